@@ -0,0 +1,142 @@
+use bevy::{ecs::system::SystemParam, input::InputSystem, prelude::*, utils::HashSet};
+use cycle_game::{
+    bindings::{Action, Binding, Bindings, STICK_THRESHOLD},
+    sim::Direction,
+};
+
+pub struct ActionsPlugin;
+
+impl Plugin for ActionsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Bindings>()
+            .init_resource::<ActionState>()
+            .add_systems(PreUpdate, update_action_state.after(InputSystem));
+    }
+}
+
+/// Which actions are held down this frame, whatever they are bound to.
+#[derive(Resource, Debug, Default)]
+pub struct ActionState {
+    pressed: HashSet<Action>,
+    just_pressed: HashSet<Action>,
+}
+
+impl ActionState {
+    pub fn pressed(&self, action: Action) -> bool {
+        self.pressed.contains(&action)
+    }
+
+    /// True on the first frame an action is held.
+    pub fn just_pressed(&self, action: Action) -> bool {
+        self.just_pressed.contains(&action)
+    }
+
+    /// The direction being moved in, if any. Earlier directions win when
+    /// several are held.
+    pub fn pressed_direction(&self) -> Option<Direction> {
+        MOVEMENT_ACTIONS
+            .iter()
+            .find(|(action, _)| self.pressed(*action))
+            .map(|(_, direction)| *direction)
+    }
+
+    pub fn just_pressed_direction(&self) -> Option<Direction> {
+        MOVEMENT_ACTIONS
+            .iter()
+            .find(|(action, _)| self.just_pressed(*action))
+            .map(|(_, direction)| *direction)
+    }
+}
+
+/// Names the first input bound to `action`, for prompts.
+pub fn describe(bindings: &Bindings, action: Action) -> String {
+    bindings
+        .get(action)
+        .first()
+        .map_or_else(|| format!("(unbound {action})"), ToString::to_string)
+}
+
+const MOVEMENT_ACTIONS: [(Action, Direction); 4] = [
+    (Action::MoveUp, Direction::Up),
+    (Action::MoveLeft, Direction::Left),
+    (Action::MoveDown, Direction::Down),
+    (Action::MoveRight, Direction::Right),
+];
+
+const STICK_AXES: [GamepadAxisType; 4] = [
+    GamepadAxisType::LeftStickX,
+    GamepadAxisType::LeftStickY,
+    GamepadAxisType::RightStickX,
+    GamepadAxisType::RightStickY,
+];
+
+/// The keyboard and every connected gamepad.
+#[derive(SystemParam)]
+pub struct RawInputs<'w> {
+    keys: Res<'w, ButtonInput<KeyCode>>,
+    buttons: Res<'w, ButtonInput<GamepadButton>>,
+    axes: Res<'w, Axis<GamepadAxis>>,
+    gamepads: Res<'w, Gamepads>,
+}
+
+impl RawInputs<'_> {
+    pub fn is_active(&self, binding: Binding) -> bool {
+        match binding {
+            Binding::Key(key) => self.keys.pressed(key),
+            Binding::Button(button_type) => self.gamepads.iter().any(|gamepad| {
+                self.buttons
+                    .pressed(GamepadButton::new(gamepad, button_type))
+            }),
+            Binding::Stick { axis, positive } => self.gamepads.iter().any(|gamepad| {
+                self.axes
+                    .get(GamepadAxis::new(gamepad, axis))
+                    .is_some_and(|value| {
+                        if positive {
+                            value > STICK_THRESHOLD
+                        } else {
+                            value < -STICK_THRESHOLD
+                        }
+                    })
+            }),
+        }
+    }
+
+    /// Keys and buttons pressed this frame.
+    pub fn just_pressed(&self) -> impl Iterator<Item = Binding> + '_ {
+        self.keys
+            .get_just_pressed()
+            .map(|key| Binding::Key(*key))
+            .chain(
+                self.buttons
+                    .get_just_pressed()
+                    .map(|button| Binding::Button(button.button_type)),
+            )
+    }
+
+    /// Sticks currently pushed past the threshold.
+    pub fn active_sticks(&self) -> impl Iterator<Item = Binding> + '_ {
+        STICK_AXES
+            .into_iter()
+            .flat_map(|axis| [true, false].map(|positive| Binding::Stick { axis, positive }))
+            .filter(|binding| self.is_active(*binding))
+    }
+}
+
+fn update_action_state(
+    mut action_state: ResMut<ActionState>,
+    bindings: Res<Bindings>,
+    inputs: RawInputs,
+) {
+    let pressed: HashSet<Action> = Action::ALL
+        .into_iter()
+        .filter(|action| {
+            bindings
+                .get(*action)
+                .iter()
+                .any(|binding| inputs.is_active(*binding))
+        })
+        .collect();
+
+    action_state.just_pressed = pressed.difference(&action_state.pressed).copied().collect();
+    action_state.pressed = pressed;
+}
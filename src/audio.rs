@@ -0,0 +1,184 @@
+//! Ambient music that follows the clock, and sound effects for what happens
+//! in a level.
+//!
+//! The music is a stack of looping layers that all play at once: a day and a
+//! night layer that trade places over the course of the day, and one layer
+//! per season. Only their volumes change, so moving between them is always a
+//! crossfade rather than a cut.
+
+use std::time::Duration;
+
+use bevy::{audio::Volume, prelude::*, utils::HashMap};
+use cycle_game::{
+    consts,
+    save::Preferences,
+    sim::{Seasion, StepOutcome},
+};
+
+use crate::game_state::{GameState, TimeState};
+
+pub struct SoundPlugin;
+
+impl Plugin for SoundPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<SoundEffect>()
+            .add_systems(Startup, (load_sound_effects, spawn_music_layers))
+            .add_systems(OnEnter(GameState::Dead), play(SoundEffect::Death))
+            .add_systems(OnEnter(GameState::LevelComplete), play(SoundEffect::Goal))
+            .add_systems(Update, (mix_music, play_sound_effects));
+    }
+}
+
+#[derive(Event, Debug, PartialEq, Eq, Copy, Clone, Hash)]
+pub enum SoundEffect {
+    Orb,
+    Blocked,
+    Goal,
+    Death,
+}
+
+impl SoundEffect {
+    const ALL: [SoundEffect; 4] = [
+        SoundEffect::Orb,
+        SoundEffect::Blocked,
+        SoundEffect::Goal,
+        SoundEffect::Death,
+    ];
+
+    fn path(self) -> &'static str {
+        match self {
+            SoundEffect::Orb => "audio/sfx/orb.wav",
+            SoundEffect::Blocked => "audio/sfx/blocked.wav",
+            SoundEffect::Goal => "audio/sfx/goal.wav",
+            SoundEffect::Death => "audio/sfx/death.wav",
+        }
+    }
+
+    /// The sound a step makes, if any. Goals and deaths are left to the
+    /// screens they lead to.
+    pub fn for_step(outcome: &StepOutcome) -> Option<Self> {
+        if !outcome.moved {
+            Some(SoundEffect::Blocked)
+        } else if outcome.entered_orb {
+            Some(SoundEffect::Orb)
+        } else {
+            None
+        }
+    }
+}
+
+#[derive(Resource, Deref)]
+struct SoundEffects(HashMap<SoundEffect, Handle<AudioSource>>);
+
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+enum Layer {
+    Day,
+    Night,
+    Seasion(Seasion),
+}
+
+impl Layer {
+    fn all() -> impl Iterator<Item = Layer> {
+        [Layer::Day, Layer::Night]
+            .into_iter()
+            .chain(Seasion::ALL.map(Layer::Seasion))
+    }
+
+    fn path(self) -> &'static str {
+        match self {
+            Layer::Day => "audio/music/day.wav",
+            Layer::Night => "audio/music/night.wav",
+            Layer::Seasion(Seasion::Spring) => "audio/music/spring.wav",
+            Layer::Seasion(Seasion::Summer) => "audio/music/summer.wav",
+            Layer::Seasion(Seasion::Autum) => "audio/music/autumn.wav",
+            Layer::Seasion(Seasion::Winter) => "audio/music/winter.wav",
+        }
+    }
+
+    /// How loud this layer should be at this time, from 0 to 1. Day follows
+    /// the daylight, peaking at noon.
+    fn level(self, time_state: &TimeState) -> f32 {
+        match self {
+            Layer::Day => time_state.daylight(),
+            Layer::Night => 1.0 - time_state.daylight(),
+            Layer::Seasion(seasion) => (time_state.current_seasion() == seasion) as i32 as f32,
+        }
+    }
+}
+
+/// One of the looping music layers, and how loud it is playing right now.
+#[derive(Component)]
+struct MusicLayer {
+    layer: Layer,
+    level: f32,
+}
+
+fn load_sound_effects(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.insert_resource(SoundEffects(
+        SoundEffect::ALL
+            .into_iter()
+            .map(|effect| (effect, asset_server.load(effect.path())))
+            .collect(),
+    ));
+}
+
+fn spawn_music_layers(mut commands: Commands, asset_server: Res<AssetServer>) {
+    for layer in Layer::all() {
+        commands.spawn((
+            AudioBundle {
+                source: asset_server.load(layer.path()),
+                settings: PlaybackSettings::LOOP.with_volume(Volume::ZERO),
+            },
+            MusicLayer { layer, level: 0.0 },
+        ));
+    }
+}
+
+/// Eases every layer towards the level the clock asks for. The global volume
+/// only applies when a sound starts, so the overall volume is folded in here
+/// for the music, which never restarts.
+fn mix_music(
+    time: Res<Time>,
+    time_state: Res<TimeState>,
+    preferences: Res<Preferences>,
+    mut layers: Query<(&mut MusicLayer, &AudioSink)>,
+) {
+    let max_change = time.delta_seconds() / consts::MUSIC_CROSSFADE_SECONDS;
+    for (mut music_layer, sink) in &mut layers {
+        let target = music_layer.layer.level(&time_state);
+        music_layer.level += (target - music_layer.level).clamp(-max_change, max_change);
+        sink.set_volume(music_layer.level * preferences.music_volume * preferences.volume);
+    }
+}
+
+fn play(effect: SoundEffect) -> impl Fn(EventWriter<SoundEffect>) {
+    move |mut sound_effects| {
+        sound_effects.send(effect);
+    }
+}
+
+/// Plays each sound effect asked for, skipping repeats that come too soon
+/// after the last, such as when walking into a wall for several frames.
+fn play_sound_effects(
+    mut commands: Commands,
+    mut requests: EventReader<SoundEffect>,
+    mut last_played: Local<HashMap<SoundEffect, Duration>>,
+    sound_effects: Res<SoundEffects>,
+    preferences: Res<Preferences>,
+    time: Res<Time>,
+) {
+    for &effect in requests.read() {
+        let now = time.elapsed();
+        if last_played.get(&effect).is_some_and(|&played| {
+            now - played < Duration::from_secs_f32(consts::SOUND_EFFECT_REPEAT_SECONDS)
+        }) {
+            continue;
+        }
+        last_played.insert(effect, now);
+
+        commands.spawn(AudioBundle {
+            source: sound_effects[&effect].clone(),
+            settings: PlaybackSettings::DESPAWN.with_volume(Volume::new(preferences.sfx_volume)),
+        });
+    }
+}
@@ -0,0 +1,77 @@
+//! Plays recorded replays against the current rules and checks each one still
+//! reaches the goal. Keep replays of known solutions around and run this
+//! after changing the rules.
+//!
+//! Usage: `cargo run --bin play_replays [--project path/to/project.ldtk] replay.json...`
+//!
+//! Exits with a non-zero status if any replay fails to finish its level.
+
+use anyhow::{bail, Context, Result};
+use cycle_game::sim::{self, Replay};
+
+const DEFAULT_PROJECT: &str = "assets/tile-based-game.ldtk";
+
+fn main() -> Result<()> {
+    let mut project_path = DEFAULT_PROJECT.to_string();
+    let mut replay_paths = Vec::new();
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        if arg == "--project" {
+            project_path = args.next().context("--project needs a path")?;
+        } else {
+            replay_paths.push(arg);
+        }
+    }
+    if replay_paths.is_empty() {
+        bail!("no replays given");
+    }
+
+    let project = sim::load_project(&project_path)?;
+    let orb_registry = sim::OrbRegistry::default();
+
+    let mut all_finished = true;
+    for replay_path in &replay_paths {
+        let json = std::fs::read_to_string(replay_path)
+            .with_context(|| format!("couldn't read {replay_path}"))?;
+        let replay =
+            Replay::from_json(&json).with_context(|| format!("couldn't read {replay_path}"))?;
+        let ldtk_level = project
+            .levels
+            .iter()
+            .find(|level| level.iid == replay.level_iid)
+            .with_context(|| {
+                format!(
+                    "{replay_path}: level {} isn't in the project",
+                    replay.level_iid
+                )
+            })?;
+
+        let world = replay.play(sim::Level::from_ldtk(ldtk_level, &orb_registry)?);
+        let grid_coords = world.player.grid_coords;
+        let time_state = world.time_state;
+
+        if let Some(cause) = world.level.death_cause(&grid_coords, &time_state) {
+            all_finished = false;
+            println!("{replay_path}: {} failed, {cause}", ldtk_level.identifier);
+        } else if world.level.goal_reached(&grid_coords, &time_state) {
+            println!(
+                "{replay_path}: {} finished at {}:00 in {} (time {})",
+                ldtk_level.identifier,
+                time_state.current_hour(),
+                time_state.current_seasion(),
+                time_state.time,
+            );
+        } else {
+            all_finished = false;
+            println!(
+                "{replay_path}: {} ended away from the goal at {:?}",
+                ldtk_level.identifier, grid_coords
+            );
+        }
+    }
+
+    if !all_finished {
+        std::process::exit(1);
+    }
+    Ok(())
+}
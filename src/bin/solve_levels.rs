@@ -0,0 +1,82 @@
+//! Searches every level in an LDtk project for a shortest route to the goal.
+//!
+//! Usage: `cargo run --bin solve_levels [path/to/project.ldtk]`
+//!
+//! Exits with a non-zero status if any level can't be shown to be solvable.
+
+use anyhow::Result;
+use cycle_game::{
+    consts,
+    sim::{self, Direction, SolverResult, TimeState},
+};
+
+const DEFAULT_PROJECT: &str = "assets/tile-based-game.ldtk";
+
+fn main() -> Result<()> {
+    let path = std::env::args()
+        .nth(1)
+        .unwrap_or_else(|| DEFAULT_PROJECT.to_string());
+    let project = sim::load_project(&path)?;
+    let orb_registry = sim::OrbRegistry::default();
+
+    let mut all_solved = true;
+    // Levels are played in order, so each can start from any clock the last
+    // one can be finished with, in any movement mode.
+    let mut arrivals = vec![TimeState::default()];
+    for ldtk_level in &project.levels {
+        let level = sim::Level::from_ldtk(ldtk_level, &orb_registry)?;
+        let mut finishes = Vec::new();
+        for start in level.starts(&arrivals) {
+            for mode in level.movement_modes() {
+                let name = format!(
+                    "{} ({mode:?}, from time {})",
+                    ldtk_level.identifier, start.time
+                );
+                match sim::solve(&level, start, mode, consts::SOLVER_MAX_STATES) {
+                    SolverResult::Solved(solution) => {
+                        let time_state = solution.time_state;
+                        println!(
+                            "{name}: solved in {} moves, arriving at {}:00 in {} (time {})",
+                            solution.moves.len(),
+                            time_state.current_hour(),
+                            time_state.current_seasion(),
+                            time_state.time,
+                        );
+                        println!("  {}", route(&solution.moves));
+                        if !finishes.contains(&time_state) {
+                            finishes.push(time_state);
+                        }
+                    }
+                    SolverResult::Unsolvable => {
+                        all_solved = false;
+                        println!("{name}: can't be solved");
+                    }
+                    SolverResult::GaveUp => {
+                        all_solved = false;
+                        println!("{name}: gave up after {} states", consts::SOLVER_MAX_STATES);
+                    }
+                }
+            }
+        }
+        if !finishes.is_empty() {
+            arrivals = finishes;
+        }
+    }
+
+    if !all_solved {
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+fn route(moves: &[Direction]) -> String {
+    moves
+        .iter()
+        .map(|direction| match direction {
+            Direction::Up => 'U',
+            Direction::Down => 'D',
+            Direction::Left => 'L',
+            Direction::Right => 'R',
+        })
+        .collect()
+}
@@ -0,0 +1,49 @@
+//! Checks every level in an LDtk project for mistakes that would otherwise
+//! only show up when the level is played.
+//!
+//! Usage: `cargo run --bin validate_levels [path/to/project.ldtk]`
+//!
+//! Exits with a non-zero status if any level has a problem, so it can gate
+//! level changes.
+
+use anyhow::Result;
+use cycle_game::{consts, sim};
+
+const DEFAULT_PROJECT: &str = "assets/tile-based-game.ldtk";
+
+fn main() -> Result<()> {
+    let path = std::env::args()
+        .nth(1)
+        .unwrap_or_else(|| DEFAULT_PROJECT.to_string());
+    let project = sim::load_project(&path)?;
+    let orb_registry = sim::OrbRegistry::default();
+
+    let mut problem_count = 0;
+    // Levels are played in order, so each can start from any clock the last
+    // one can be finished with, in any movement mode.
+    let mut arrivals = vec![sim::TimeState::default()];
+    for level in &project.levels {
+        let validation =
+            sim::validate_level(level, &orb_registry, &arrivals, consts::SOLVER_MAX_STATES);
+        let problems = validation.problems;
+        if !validation.finishes.is_empty() {
+            arrivals = validation.finishes;
+        }
+        if problems.is_empty() {
+            println!("{}: ok", level.identifier);
+            continue;
+        }
+
+        println!("{}:", level.identifier);
+        for problem in &problems {
+            println!("  {problem}");
+        }
+        problem_count += problems.len();
+    }
+
+    if problem_count > 0 {
+        eprintln!("found {problem_count} problem(s) in {path}");
+        std::process::exit(1);
+    }
+    Ok(())
+}
@@ -0,0 +1,205 @@
+//! The actions the game responds to and the inputs bound to them.
+
+use std::collections::BTreeMap;
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// How far a stick has to be pushed to count as pressed.
+pub const STICK_THRESHOLD: f32 = 0.5;
+
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Copy, Clone, Hash, Serialize, Deserialize)]
+pub enum Action {
+    MoveUp,
+    MoveDown,
+    MoveLeft,
+    MoveRight,
+    Confirm,
+    /// Backs out of a menu.
+    Cancel,
+    Undo,
+    Redo,
+    Restart,
+    Pause,
+    ToggleMovement,
+    /// Opens the controls screen from the title screen.
+    Controls,
+    /// Shows or hides what each move would do to the clock.
+    TogglePreview,
+    /// Saves a replay of the current level so far.
+    SaveReplay,
+    /// Restarts the saved replay's level and plays the replay on it.
+    PlayReplay,
+    /// Hands control back to the player during a replay.
+    StopReplay,
+    FastForwardReplay,
+    PauseReplay,
+    /// Plays the next input of a paused replay.
+    StepReplay,
+}
+
+impl Action {
+    pub const ALL: [Action; 19] = [
+        Action::MoveUp,
+        Action::MoveDown,
+        Action::MoveLeft,
+        Action::MoveRight,
+        Action::Confirm,
+        Action::Cancel,
+        Action::Undo,
+        Action::Redo,
+        Action::Restart,
+        Action::Pause,
+        Action::ToggleMovement,
+        Action::Controls,
+        Action::TogglePreview,
+        Action::SaveReplay,
+        Action::PlayReplay,
+        Action::StopReplay,
+        Action::FastForwardReplay,
+        Action::PauseReplay,
+        Action::StepReplay,
+    ];
+}
+
+impl std::fmt::Display for Action {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Action::MoveUp => "move up",
+            Action::MoveDown => "move down",
+            Action::MoveLeft => "move left",
+            Action::MoveRight => "move right",
+            Action::Confirm => "confirm",
+            Action::Cancel => "cancel",
+            Action::Undo => "undo",
+            Action::Redo => "redo",
+            Action::Restart => "restart",
+            Action::Pause => "pause",
+            Action::ToggleMovement => "toggle movement",
+            Action::Controls => "controls",
+            Action::TogglePreview => "toggle time preview",
+            Action::SaveReplay => "save replay",
+            Action::PlayReplay => "play replay",
+            Action::StopReplay => "stop replay",
+            Action::FastForwardReplay => "fast-forward replay",
+            Action::PauseReplay => "pause replay",
+            Action::StepReplay => "step replay",
+        })
+    }
+}
+
+/// One input that can trigger an action. Buttons and sticks work on any
+/// connected gamepad.
+#[derive(Debug, PartialEq, Eq, Copy, Clone, Hash, Serialize, Deserialize)]
+pub enum Binding {
+    Key(KeyCode),
+    Button(GamepadButtonType),
+    /// A stick pushed past [`STICK_THRESHOLD`] along `axis`, in the positive
+    /// direction if `positive` is set.
+    Stick {
+        axis: GamepadAxisType,
+        positive: bool,
+    },
+}
+
+impl std::fmt::Display for Binding {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Binding::Key(key) => {
+                let name = format!("{key:?}");
+                f.write_str(
+                    name.strip_prefix("Key")
+                        .or_else(|| name.strip_prefix("Arrow"))
+                        .unwrap_or(&name),
+                )
+            }
+            Binding::Button(button) => write!(f, "pad {button:?}"),
+            Binding::Stick { axis, positive } => {
+                write!(f, "{axis:?}{}", if *positive { "+" } else { "-" })
+            }
+        }
+    }
+}
+
+/// The inputs bound to each action.
+#[derive(Resource, Debug, Clone, PartialEq, Deref, DerefMut, Serialize, Deserialize)]
+pub struct Bindings(BTreeMap<Action, Vec<Binding>>);
+
+impl Bindings {
+    pub fn get(&self, action: Action) -> &[Binding] {
+        self.0.get(&action).map_or(&[], Vec::as_slice)
+    }
+
+    /// The bindings an action starts out with.
+    pub fn defaults(action: Action) -> Vec<Binding> {
+        use Binding::{Button, Key, Stick};
+        use GamepadAxisType::{LeftStickX, LeftStickY};
+        use GamepadButtonType as Pad;
+
+        match action {
+            Action::MoveUp => vec![
+                Key(KeyCode::KeyW),
+                Key(KeyCode::ArrowUp),
+                Button(Pad::DPadUp),
+                Stick {
+                    axis: LeftStickY,
+                    positive: true,
+                },
+            ],
+            Action::MoveDown => vec![
+                Key(KeyCode::KeyS),
+                Key(KeyCode::ArrowDown),
+                Button(Pad::DPadDown),
+                Stick {
+                    axis: LeftStickY,
+                    positive: false,
+                },
+            ],
+            Action::MoveLeft => vec![
+                Key(KeyCode::KeyA),
+                Key(KeyCode::ArrowLeft),
+                Button(Pad::DPadLeft),
+                Stick {
+                    axis: LeftStickX,
+                    positive: false,
+                },
+            ],
+            Action::MoveRight => vec![
+                Key(KeyCode::KeyD),
+                Key(KeyCode::ArrowRight),
+                Button(Pad::DPadRight),
+                Stick {
+                    axis: LeftStickX,
+                    positive: true,
+                },
+            ],
+            Action::Confirm => vec![Key(KeyCode::Space), Key(KeyCode::Enter), Button(Pad::South)],
+            Action::Cancel => vec![Key(KeyCode::Backspace), Button(Pad::East)],
+            Action::Undo => vec![Key(KeyCode::KeyZ), Button(Pad::LeftTrigger)],
+            Action::Redo => vec![Key(KeyCode::KeyY), Button(Pad::RightTrigger)],
+            Action::Restart => vec![Key(KeyCode::KeyR), Button(Pad::West)],
+            Action::Pause => vec![Key(KeyCode::Escape), Button(Pad::Start)],
+            Action::ToggleMovement => vec![Key(KeyCode::KeyM), Button(Pad::North)],
+            Action::Controls => vec![Key(KeyCode::KeyC), Button(Pad::Select)],
+            Action::TogglePreview => vec![Key(KeyCode::KeyT), Button(Pad::RightThumb)],
+            // Replays are a debugging aid, so they stay on the keyboard.
+            Action::SaveReplay => vec![Key(KeyCode::F5)],
+            Action::PlayReplay => vec![Key(KeyCode::F9)],
+            Action::StopReplay => vec![Key(KeyCode::Delete)],
+            Action::FastForwardReplay => vec![Key(KeyCode::Tab)],
+            Action::PauseReplay => vec![Key(KeyCode::KeyP)],
+            Action::StepReplay => vec![Key(KeyCode::Period)],
+        }
+    }
+}
+
+impl Default for Bindings {
+    fn default() -> Self {
+        Self(
+            Action::ALL
+                .into_iter()
+                .map(|action| (action, Bindings::defaults(action)))
+                .collect(),
+        )
+    }
+}
@@ -0,0 +1,100 @@
+use bevy::prelude::*;
+use bevy_ecs_ldtk::{prelude::*, utils::grid_coords_to_translation};
+use cycle_game::{
+    consts,
+    sim::{identifiers, BlockKind, LevelState},
+};
+
+use crate::{
+    game_state::{GameState, TimeState},
+    level::CurrentLevel,
+    player::TileTween,
+};
+
+/// The colour a wooden crate fades to as it is about to rot away.
+const ROTTEN_COLOR: Color = Color::srgb(0.35, 0.3, 0.2);
+
+#[derive(Default, Component)]
+pub struct Block;
+
+/// Crates and ice blocks spawn the same way, what they are made of lives in
+/// the rules.
+#[derive(Default, Bundle, LdtkEntity)]
+struct BlockBundle {
+    block: Block,
+    #[sprite_sheet_bundle]
+    sprite_sheet_bundle: LdtkSpriteSheetBundle,
+    #[grid_coords]
+    grid_coords: GridCoords,
+}
+
+pub struct BlockPlugin;
+
+impl Plugin for BlockPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_ldtk_entity::<BlockBundle>(identifiers::CRATE)
+            .register_ldtk_entity::<BlockBundle>(identifiers::ICE_BLOCK)
+            .add_systems(Update, place_blocks.run_if(in_state(GameState::Playing)));
+    }
+}
+
+/// Moves blocks to where they have been pushed, sliding them along when they
+/// move one tile, and hides the ones that have rotted or melted away.
+fn place_blocks(
+    mut commands: Commands,
+    time_state: Res<TimeState>,
+    level_state: Res<LevelState>,
+    current_level: Res<CurrentLevel>,
+    mut blocks: Query<
+        (
+            Entity,
+            &EntityIid,
+            &mut GridCoords,
+            &mut Transform,
+            &mut Visibility,
+            &mut Sprite,
+        ),
+        With<Block>,
+    >,
+) {
+    for (entity, iid, mut grid_coords, mut transform, mut visibility, mut sprite) in &mut blocks {
+        let Some(index) = current_level
+            .blocks
+            .iter()
+            .position(|block| block.iid == iid.as_str())
+        else {
+            continue;
+        };
+
+        visibility.set_if_neq(
+            if current_level.block_present(index, &level_state, &time_state) {
+                Visibility::Inherited
+            } else {
+                Visibility::Hidden
+            },
+        );
+
+        if let BlockKind::Wooden { rots_after } = current_level.blocks[index].kind {
+            let age = time_state.days_since_start() as f32 / rots_after.max(1) as f32;
+            sprite.color = Color::WHITE.mix(&ROTTEN_COLOR, age.clamp(0.0, 1.0));
+        }
+
+        let position = level_state.blocks[index].grid_coords;
+        if *grid_coords == position {
+            continue;
+        }
+
+        let translation = grid_coords_to_translation(position, IVec2::splat(consts::GRID_SIZE));
+        let step = IVec2::from(position) - IVec2::from(*grid_coords);
+        if step.abs().element_sum() == 1 {
+            commands
+                .entity(entity)
+                .insert(TileTween::new(transform.translation.xy(), translation));
+        } else {
+            // Undo can move a block any distance, so it goes straight there.
+            commands.entity(entity).remove::<TileTween>();
+            transform.translation = translation.extend(transform.translation.z);
+        }
+        *grid_coords = position;
+    }
+}
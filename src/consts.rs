@@ -3,4 +3,11 @@ pub const ASPECT_RATIO: f32 = 16. / 9.;
 pub const DAYS_PER_SEASION: i32 = 2;
 pub const BASE_LIGHT: f32 = 0.05;
 pub const MOVEMENT_SPEED: f64 = 1.0;
+pub const SAPLING_SPEED_FACTOR: f64 = 0.5;
 pub const TIME_STEP_INCREMENT: f64 = 1.0;
+pub const UNDO_HISTORY_SIZE: usize = 256;
+pub const TILE_TWEEN_SECONDS: f32 = 0.15;
+pub const SOLVER_MAX_STATES: usize = 1_000_000;
+pub const REPLAY_FAST_FORWARD_INPUTS: usize = 8;
+pub const MUSIC_CROSSFADE_SECONDS: f32 = 2.0;
+pub const SOUND_EFFECT_REPEAT_SECONDS: f32 = 0.25;
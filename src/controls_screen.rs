@@ -0,0 +1,207 @@
+use bevy::{prelude::*, utils::HashSet};
+use cycle_game::bindings::{Action, Binding, Bindings};
+
+use crate::{
+    actions::{self, ActionState, RawInputs},
+    game_state::{ControlsOpen, GameState, PauseMenu},
+    pause::OVERLAY_BACKGROUND,
+};
+
+const SELECTED_COLOR: Color = Color::srgb(1.0, 0.85, 0.2);
+
+pub struct ControlsScreenPlugin;
+
+impl Plugin for ControlsScreenPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(ControlsOpen), spawn_controls_screen)
+            .add_systems(OnExit(ControlsOpen), despawn_controls_screen)
+            .add_systems(
+                Update,
+                (controls_screen_key_press, capture_binding, update_rows)
+                    .chain()
+                    .run_if(in_state(ControlsOpen)),
+            );
+    }
+}
+
+#[derive(Component)]
+pub struct ControlsScreen;
+
+#[derive(Component)]
+pub struct ControlsRow(Action);
+
+/// Which row is picked, and whether the next input pressed gets bound to it.
+#[derive(Resource, Default)]
+struct ControlsCursor {
+    selected: usize,
+    capturing: bool,
+    /// Set from the frame after capturing starts, so the press that started
+    /// it isn't captured.
+    armed: bool,
+    /// Sticks already pushed when capturing started, which shouldn't count.
+    held_sticks: HashSet<Binding>,
+}
+
+/// Binds the first key, button or stick pressed while capturing.
+fn capture_binding(
+    mut cursor: ResMut<ControlsCursor>,
+    mut bindings: ResMut<Bindings>,
+    inputs: RawInputs,
+) {
+    if !cursor.capturing {
+        return;
+    }
+    if !cursor.armed {
+        cursor.armed = true;
+        return;
+    }
+
+    let pressed = inputs.just_pressed().next().or_else(|| {
+        inputs
+            .active_sticks()
+            .find(|stick| !cursor.held_sticks.contains(stick))
+    });
+
+    if let Some(binding) = pressed {
+        let action = Action::ALL[cursor.selected];
+        let action_bindings = bindings.entry(action).or_default();
+        if !action_bindings.contains(&binding) {
+            action_bindings.push(binding);
+        }
+        cursor.capturing = false;
+    }
+}
+
+/// Up and down pick an action. Confirm adds a binding to it, undo removes
+/// its last one, restart puts back its defaults and cancel goes back to
+/// wherever the screen was opened from.
+fn controls_screen_key_press(
+    game_state: Res<State<GameState>>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut next_pause_menu: ResMut<NextState<PauseMenu>>,
+    mut cursor: ResMut<ControlsCursor>,
+    mut bindings: ResMut<Bindings>,
+    actions: Res<ActionState>,
+    inputs: RawInputs,
+) {
+    if cursor.capturing {
+        return;
+    }
+
+    let action = Action::ALL[cursor.selected];
+    if actions.just_pressed(Action::MoveUp) {
+        cursor.selected = cursor
+            .selected
+            .checked_sub(1)
+            .unwrap_or(Action::ALL.len() - 1);
+    } else if actions.just_pressed(Action::MoveDown) {
+        cursor.selected = (cursor.selected + 1) % Action::ALL.len();
+    } else if actions.just_pressed(Action::Confirm) {
+        cursor.capturing = true;
+        cursor.armed = false;
+        cursor.held_sticks = inputs.active_sticks().collect();
+    } else if actions.just_pressed(Action::Undo) {
+        bindings.entry(action).or_default().pop();
+    } else if actions.just_pressed(Action::Restart) {
+        bindings.insert(action, Bindings::defaults(action));
+    } else if actions.just_pressed(Action::Cancel) {
+        if *game_state.get() == GameState::Controls {
+            next_state.set(GameState::WelcomeScreen);
+        } else {
+            next_pause_menu.set(PauseMenu::Settings);
+        }
+    }
+}
+
+fn update_rows(
+    cursor: Res<ControlsCursor>,
+    bindings: Res<Bindings>,
+    mut rows: Query<(&ControlsRow, &mut Text)>,
+) {
+    for (ControlsRow(action), mut text) in &mut rows {
+        let selected = Action::ALL[cursor.selected] == *action;
+        let section = &mut text.sections[0];
+        section.style.color = if selected {
+            SELECTED_COLOR
+        } else {
+            Color::WHITE
+        };
+        section.value = if selected && cursor.capturing {
+            format!("{action}: press something to bind")
+        } else {
+            let names: Vec<String> = bindings
+                .get(*action)
+                .iter()
+                .map(ToString::to_string)
+                .collect();
+            format!("{action}: {}", names.join(", "))
+        };
+    }
+}
+
+fn spawn_controls_screen(mut commands: Commands, bindings: Res<Bindings>) {
+    commands.init_resource::<ControlsCursor>();
+
+    let container = NodeBundle {
+        style: Style {
+            width: Val::Percent(100.0),
+            height: Val::Percent(100.0),
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            flex_direction: FlexDirection::Column,
+            ..default()
+        },
+        background_color: OVERLAY_BACKGROUND.into(),
+        ..default()
+    };
+
+    commands
+        .spawn((container, ControlsScreen))
+        .with_children(|screen| {
+            screen.spawn(TextBundle::from_section(
+                "Controls",
+                TextStyle {
+                    font_size: 60.0,
+                    color: Color::WHITE,
+                    ..default()
+                },
+            ));
+
+            for action in Action::ALL {
+                screen.spawn((
+                    TextBundle::from_section(
+                        "",
+                        TextStyle {
+                            font_size: 24.0,
+                            color: Color::WHITE,
+                            ..default()
+                        },
+                    ),
+                    ControlsRow(action),
+                ));
+            }
+
+            screen.spawn(TextBundle::from_section(
+                format!(
+                    "{} adds a binding, {} removes one, {} resets, {} goes back",
+                    actions::describe(&bindings, Action::Confirm),
+                    actions::describe(&bindings, Action::Undo),
+                    actions::describe(&bindings, Action::Restart),
+                    actions::describe(&bindings, Action::Cancel),
+                ),
+                TextStyle {
+                    font_size: 20.0,
+                    color: Color::WHITE,
+                    ..default()
+                },
+            ));
+        });
+}
+
+fn despawn_controls_screen(mut commands: Commands, query: Query<Entity, With<ControlsScreen>>) {
+    commands.remove_resource::<ControlsCursor>();
+    let Ok(controls_screen) = query.get_single() else {
+        return;
+    };
+    commands.entity(controls_screen).despawn_recursive();
+}
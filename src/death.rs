@@ -0,0 +1,147 @@
+use bevy::prelude::*;
+use bevy_ecs_ldtk::prelude::*;
+use cycle_game::{
+    bindings::{Action, Bindings},
+    sim::DeathCause,
+};
+
+use crate::{
+    actions::{self, ActionState},
+    game_state::{GameState, TimeState},
+    level::{cache_current_level, CurrentLevel, QuitToTitle, RestartLevel},
+    player::Player,
+};
+
+pub struct DeathPlugin;
+
+impl Plugin for DeathPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            check_failure
+                .after(cache_current_level)
+                .run_if(in_state(GameState::Playing)),
+        )
+        .add_systems(OnEnter(GameState::Dead), spawn_death_screen)
+        .add_systems(OnExit(GameState::Dead), despawn_death_screen)
+        .add_systems(
+            Update,
+            death_screen_key_press.run_if(in_state(GameState::Dead)),
+        );
+    }
+}
+
+/// What killed the player last.
+#[derive(Resource, Debug, Deref)]
+pub struct CauseOfDeath(DeathCause);
+
+#[derive(Component)]
+pub struct DeathScreen;
+
+/// Ends the level when the player stands somewhere deadly. Checked whenever
+/// the player or the clock moves, since a deadline can pass while the player
+/// stays on one tile.
+fn check_failure(
+    mut commands: Commands,
+    mut next_state: ResMut<NextState<GameState>>,
+    players: Query<Ref<GridCoords>, With<Player>>,
+    time_state: Res<TimeState>,
+    current_level: Res<CurrentLevel>,
+) {
+    for player_grid_coords in &players {
+        if !(player_grid_coords.is_changed() || time_state.is_changed()) {
+            continue;
+        }
+
+        if let Some(cause) = current_level.death_cause(&player_grid_coords, &time_state) {
+            commands.insert_resource(CauseOfDeath(cause));
+            next_state.set(GameState::Dead);
+        }
+    }
+}
+
+/// Restart restarts the level, cancel goes back to the title screen.
+fn death_screen_key_press(
+    mut next_state: ResMut<NextState<GameState>>,
+    mut restarts: EventWriter<RestartLevel>,
+    mut quits: EventWriter<QuitToTitle>,
+    actions: Res<ActionState>,
+) {
+    if actions.just_pressed(Action::Restart) {
+        restarts.send_default();
+        next_state.set(GameState::Playing);
+    } else if actions.just_pressed(Action::Cancel) {
+        quits.send_default();
+    }
+}
+
+fn spawn_death_screen(mut commands: Commands, cause: Res<CauseOfDeath>, bindings: Res<Bindings>) {
+    let container = NodeBundle {
+        style: Style {
+            width: Val::Percent(100.0),
+            height: Val::Percent(100.0),
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            flex_direction: FlexDirection::Column,
+            ..default()
+        },
+        ..default()
+    };
+
+    let header = commands
+        .spawn(
+            TextBundle::from_sections([TextSection::new(
+                "You died",
+                TextStyle {
+                    font_size: 100.0,
+                    color: Color::WHITE,
+                    ..default()
+                },
+            )])
+            .with_text_justify(JustifyText::Center),
+        )
+        .id();
+
+    let cause = commands
+        .spawn(
+            TextBundle::from_sections([TextSection::new(
+                cause.to_string(),
+                TextStyle {
+                    font_size: 40.0,
+                    color: Color::WHITE,
+                    ..default()
+                },
+            )])
+            .with_text_justify(JustifyText::Center),
+        )
+        .id();
+
+    let prompt = commands
+        .spawn(
+            TextBundle::from_sections([TextSection::new(
+                format!(
+                    "press {} to restart the level, or {} for the menu",
+                    actions::describe(&bindings, Action::Restart),
+                    actions::describe(&bindings, Action::Cancel)
+                ),
+                TextStyle {
+                    font_size: 30.0,
+                    color: Color::WHITE,
+                    ..default()
+                },
+            )])
+            .with_text_justify(JustifyText::Center),
+        )
+        .id();
+
+    let parent = commands.spawn((container, DeathScreen)).id();
+
+    commands
+        .entity(parent)
+        .push_children(&[header, cause, prompt]);
+}
+
+fn despawn_death_screen(mut commands: Commands, query: Query<Entity, With<DeathScreen>>) {
+    let death_screen = commands.entity(query.single());
+    death_screen.despawn_recursive();
+}
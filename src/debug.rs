@@ -3,6 +3,7 @@ use bevy::prelude::*;
 pub struct DebugPlugin;
 
 impl Plugin for DebugPlugin {
+    #[cfg_attr(not(feature = "inspector"), allow(unused_variables))]
     fn build(&self, app: &mut App) {
         #[cfg(feature = "inspector")]
         {
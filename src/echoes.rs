@@ -0,0 +1,90 @@
+//! Echoes of the player, drawn wherever the player stood before at the time
+//! the clock now shows.
+
+use bevy::prelude::*;
+use bevy_ecs_ldtk::{prelude::*, utils::grid_coords_to_translation};
+use cycle_game::{consts, sim::LevelState};
+
+use crate::{
+    game_state::{GameState, TimeState},
+    level::CurrentLevel,
+    player::Player,
+};
+
+/// Echoes look like a faded copy of the player.
+const ECHO_COLOR: Color = Color::srgba(0.6, 0.8, 1.0, 0.45);
+
+/// How far behind the player echoes are drawn, so the player stays on top.
+const ECHO_DEPTH: f32 = 0.1;
+
+#[derive(Component)]
+pub struct Echo;
+
+pub struct EchoPlugin;
+
+impl Plugin for EchoPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, place_echoes.run_if(in_state(GameState::Playing)));
+    }
+}
+
+/// Keeps one echo sprite per echo in the rules, reusing the sprites already
+/// spawned and copying the player's current animation frame onto them.
+fn place_echoes(
+    mut commands: Commands,
+    current_level: Res<CurrentLevel>,
+    time_state: Res<TimeState>,
+    level_state: Res<LevelState>,
+    players: Query<
+        (
+            &GridCoords,
+            &Transform,
+            &Sprite,
+            &Handle<Image>,
+            &TextureAtlas,
+            &Parent,
+        ),
+        (With<Player>, Without<Echo>),
+    >,
+    mut echoes: Query<(Entity, &mut Transform, &mut TextureAtlas), (With<Echo>, Without<Player>)>,
+) {
+    let Ok((player, player_transform, player_sprite, texture, atlas, parent)) =
+        players.get_single()
+    else {
+        return;
+    };
+
+    let mut spawned = echoes.iter_mut();
+    for grid_coords in current_level.echoes(&level_state, &time_state, player) {
+        let translation = grid_coords_to_translation(grid_coords, IVec2::splat(consts::GRID_SIZE))
+            .extend(player_transform.translation.z - ECHO_DEPTH);
+
+        if let Some((_, mut transform, mut echo_atlas)) = spawned.next() {
+            transform.translation = translation;
+            echo_atlas.index = atlas.index;
+            continue;
+        }
+
+        commands.entity(parent.get()).with_children(|layer| {
+            layer.spawn((
+                SpriteBundle {
+                    sprite: Sprite {
+                        color: ECHO_COLOR,
+                        custom_size: player_sprite.custom_size,
+                        anchor: player_sprite.anchor,
+                        ..default()
+                    },
+                    texture: texture.clone(),
+                    transform: Transform::from_translation(translation),
+                    ..default()
+                },
+                atlas.clone(),
+                Echo,
+            ));
+        });
+    }
+
+    for (entity, ..) in spawned {
+        commands.entity(entity).despawn_recursive();
+    }
+}
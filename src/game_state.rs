@@ -1,7 +1,7 @@
 use bevy::prelude::*;
-use bevy_ecs_ldtk::{app::LdtkIntCellAppExt, LevelSelection};
+use bevy_ecs_ldtk::LevelSelection;
 
-use crate::consts::DAYS_PER_SEASION;
+pub use cycle_game::sim::TimeState;
 
 #[derive(Resource, Debug, Clone, Copy, Default, Eq, PartialEq, Hash, States)]
 pub enum GameState {
@@ -9,79 +9,56 @@ pub enum GameState {
     WelcomeScreen,
     Playing,
     Dead,
+    LevelComplete,
+    /// Every level in the project is complete.
+    Victory,
+    /// The screen for rebinding controls, opened from the title screen.
+    Controls,
 }
 
-pub struct GameStatePlugin;
-
-impl Plugin for GameStatePlugin {
-    fn build(&self, app: &mut App) {
-        app.init_state::<GameState>()
-            .init_resource::<TimeState>()
-            .insert_resource(LevelSelection::index(0));
-    }
-}
-
-#[derive(Default, Debug, PartialEq, Eq, Copy, Clone, Hash)]
-pub enum TimeAxis {
+/// Whether play is running or paused. Only exists while
+/// [`GameState::Playing`].
+#[derive(SubStates, Debug, Clone, Copy, Default, Eq, PartialEq, Hash)]
+#[source(GameState = GameState::Playing)]
+pub enum PlayState {
     #[default]
-    Horizontal,
-    Vertical,
-    None,
-}
-
-impl std::fmt::Display for TimeAxis {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.write_str(&format!("{self:?}"))
-    }
+    Running,
+    Paused,
 }
 
-#[derive(Resource, Debug, Clone, Copy, Eq, PartialEq, Hash)]
-pub struct TimeState {
-    pub time_axis: TimeAxis,
-    pub time_step_delta: i32,
-    pub time: i32,
+/// The page of the pause menu being shown.
+#[derive(SubStates, Debug, Clone, Copy, Default, Eq, PartialEq, Hash)]
+#[source(PlayState = PlayState::Paused)]
+pub enum PauseMenu {
+    #[default]
+    Main,
+    Settings,
+    Controls,
 }
 
-impl Default for TimeState {
-    fn default() -> Self {
-        Self {
-            time_axis: TimeAxis::Horizontal,
-            time_step_delta: 1,
-            time: 0,
-        }
-    }
-}
+/// Exists while the controls screen is open, from either the title screen or
+/// the pause menu.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub struct ControlsOpen;
 
-#[derive(Default, Debug)]
-pub enum Seasion {
-    #[default]
-    Spring,
-    Summer,
-    Autum,
-    Winter,
-}
+impl ComputedStates for ControlsOpen {
+    type SourceStates = (GameState, Option<PauseMenu>);
 
-impl std::fmt::Display for Seasion {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.write_str(&format!("{self:?}"))
+    fn compute((game_state, pause_menu): (GameState, Option<PauseMenu>)) -> Option<Self> {
+        (game_state == GameState::Controls || pause_menu == Some(PauseMenu::Controls))
+            .then_some(ControlsOpen)
     }
 }
 
-impl TimeState {
-    pub fn current_hour(&self) -> i32 {
-        (self.time / 60) % 24
-    }
-
-    pub fn current_seasion(&self) -> Seasion {
-        let day = self.time / 24 / 60;
-        let seasion_int = (self.time / (DAYS_PER_SEASION * 24 * 60)) % 4;
+pub struct GameStatePlugin;
 
-        match seasion_int {
-            0 => Seasion::Spring,
-            1 => Seasion::Summer,
-            2 => Seasion::Autum,
-            3 => Seasion::Winter,
-            _ => unreachable!("This should never happen"),
-        }
+impl Plugin for GameStatePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_state::<GameState>()
+            .add_sub_state::<PlayState>()
+            .add_sub_state::<PauseMenu>()
+            .add_computed_state::<ControlsOpen>()
+            .init_resource::<TimeState>()
+            .insert_resource(LevelSelection::index(0));
     }
 }
@@ -0,0 +1,96 @@
+use bevy::prelude::*;
+use bevy_ecs_ldtk::prelude::*;
+use cycle_game::sim::{identifiers, LevelState};
+
+use crate::{
+    game_state::{GameState, TimeState},
+    level::CurrentLevel,
+    player::Player,
+};
+
+/// How visible an open gate is, so players can still see where it will close.
+const OPEN_GATE_ALPHA: f32 = 0.25;
+
+/// The tint of a pressure plate while something stands on it.
+const HELD_PLATE_COLOR: Color = Color::srgb(0.6, 1.0, 0.6);
+
+#[derive(Default, Component)]
+pub struct TimeGate;
+
+#[derive(Default, Bundle, LdtkEntity)]
+struct TimeGateBundle {
+    time_gate: TimeGate,
+    #[sprite_sheet_bundle]
+    sprite_sheet_bundle: LdtkSpriteSheetBundle,
+    #[grid_coords]
+    grid_coords: GridCoords,
+}
+
+#[derive(Default, Component)]
+pub struct PressurePlate;
+
+#[derive(Default, Bundle, LdtkEntity)]
+struct PressurePlateBundle {
+    pressure_plate: PressurePlate,
+    #[sprite_sheet_bundle]
+    sprite_sheet_bundle: LdtkSpriteSheetBundle,
+    #[grid_coords]
+    grid_coords: GridCoords,
+}
+
+pub struct GatePlugin;
+
+impl Plugin for GatePlugin {
+    fn build(&self, app: &mut App) {
+        app.register_ldtk_entity::<TimeGateBundle>(identifiers::TIME_GATE)
+            .register_ldtk_entity::<PressurePlateBundle>(identifiers::PRESSURE_PLATE)
+            .add_systems(
+                Update,
+                (update_gate_sprites, update_plate_sprites).run_if(in_state(GameState::Playing)),
+            );
+    }
+}
+
+/// Fades gates out while they are open.
+fn update_gate_sprites(
+    time_state: Res<TimeState>,
+    level_state: Res<LevelState>,
+    current_level: Res<CurrentLevel>,
+    players: Query<&GridCoords, With<Player>>,
+    mut gates: Query<(&GridCoords, &mut Sprite), With<TimeGate>>,
+) {
+    let Ok(player) = players.get_single() else {
+        return;
+    };
+
+    for (grid_coords, mut sprite) in &mut gates {
+        let closed = current_level
+            .gate_at(grid_coords)
+            .is_some_and(|gate| current_level.gate_closed(gate, &level_state, &time_state, player));
+        sprite
+            .color
+            .set_alpha(if closed { 1.0 } else { OPEN_GATE_ALPHA });
+    }
+}
+
+/// Lights up pressure plates while the player or an echo holds them down.
+fn update_plate_sprites(
+    time_state: Res<TimeState>,
+    level_state: Res<LevelState>,
+    current_level: Res<CurrentLevel>,
+    players: Query<&GridCoords, With<Player>>,
+    mut plates: Query<(&GridCoords, &mut Sprite), With<PressurePlate>>,
+) {
+    let Ok(player) = players.get_single() else {
+        return;
+    };
+
+    for (grid_coords, mut sprite) in &mut plates {
+        let held = current_level
+            .pressure_plates
+            .iter()
+            .filter(|plate| plate.grid_coords == *grid_coords)
+            .any(|plate| current_level.plate_held(plate, &level_state, &time_state, player));
+        sprite.color = if held { HELD_PLATE_COLOR } else { Color::WHITE };
+    }
+}
@@ -1,5 +1,6 @@
 use bevy::prelude::*;
 use bevy_ecs_ldtk::prelude::*;
+use cycle_game::sim::identifiers;
 
 #[derive(Default, Component)]
 pub struct Goal;
@@ -16,6 +17,6 @@ struct GoalBundle {
 pub struct GoalPlugin;
 impl Plugin for GoalPlugin {
     fn build(&self, app: &mut App) {
-        app.register_ldtk_entity::<GoalBundle>("Goal");
+        app.register_ldtk_entity::<GoalBundle>(identifiers::GOAL);
     }
 }
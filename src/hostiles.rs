@@ -0,0 +1,84 @@
+use bevy::prelude::*;
+use bevy_ecs_ldtk::{prelude::*, utils::grid_coords_to_translation};
+use cycle_game::{consts, sim::identifiers};
+
+use crate::{
+    game_state::{GameState, TimeState},
+    level::CurrentLevel,
+};
+
+/// Patrols share the player's sprite, darkened so they read as enemies.
+const PATROL_COLOR: Color = Color::srgb(0.55, 0.2, 0.25);
+
+#[derive(Default, Component)]
+pub struct Hostile;
+
+#[derive(Default, Bundle, LdtkEntity)]
+struct HostileBundle {
+    hostile: Hostile,
+    #[sprite_sheet_bundle]
+    sprite_sheet_bundle: LdtkSpriteSheetBundle,
+    #[grid_coords]
+    grid_coords: GridCoords,
+}
+
+#[derive(Default, Component)]
+pub struct Patrol;
+
+#[derive(Default, Bundle, LdtkEntity)]
+struct PatrolBundle {
+    patrol: Patrol,
+    #[sprite_sheet_bundle]
+    sprite_sheet_bundle: LdtkSpriteSheetBundle,
+    #[grid_coords]
+    grid_coords: GridCoords,
+}
+
+pub struct HostilePlugin;
+impl Plugin for HostilePlugin {
+    fn build(&self, app: &mut App) {
+        app.register_ldtk_entity::<HostileBundle>(identifiers::HOSTILE)
+            .register_ldtk_entity::<PatrolBundle>(identifiers::PATROL)
+            .add_systems(Update, place_patrols.run_if(in_state(GameState::Playing)));
+    }
+}
+
+/// Moves each patrol to where the clock puts it, hiding the ones that are
+/// not out at this hour.
+fn place_patrols(
+    time_state: Res<TimeState>,
+    current_level: Res<CurrentLevel>,
+    mut patrols: Query<
+        (
+            &EntityIid,
+            &mut GridCoords,
+            &mut Transform,
+            &mut Visibility,
+            &mut Sprite,
+        ),
+        With<Patrol>,
+    >,
+) {
+    for (iid, mut grid_coords, mut transform, mut visibility, mut sprite) in &mut patrols {
+        sprite.color = PATROL_COLOR;
+
+        let position = current_level
+            .patrols
+            .iter()
+            .find(|patrol| patrol.iid == iid.as_str())
+            .and_then(|patrol| patrol.position(&time_state));
+
+        let Some(position) = position else {
+            visibility.set_if_neq(Visibility::Hidden);
+            continue;
+        };
+        visibility.set_if_neq(Visibility::Inherited);
+
+        if *grid_coords != position {
+            *grid_coords = position;
+            transform.translation =
+                grid_coords_to_translation(position, IVec2::splat(consts::GRID_SIZE))
+                    .extend(transform.translation.z);
+        }
+    }
+}
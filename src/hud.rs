@@ -1,20 +1,43 @@
+use std::f32::consts::{FRAC_PI_2, PI, TAU};
+
 use bevy::prelude::*;
+use cycle_game::{
+    save::{HudVerbosity, Preferences},
+    sim::{Seasion, TimeAxis},
+};
+
+use crate::{
+    game_state::{GameState, TimeState},
+    level::CurrentLevel,
+    level_complete::LevelStats,
+};
 
-use crate::game_state::{GameState, TimeState};
+const NIGHT_SKY: Color = Color::srgb(0.08, 0.1, 0.3);
+const DAY_SKY: Color = Color::srgb(0.45, 0.7, 1.0);
+
+/// How much of its colour a season that isn't the current one keeps.
+const OTHER_SEASION_ALPHA: f32 = 0.25;
 
 pub struct HudPlugin;
 
 impl Plugin for HudPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(OnEnter(GameState::Playing), spawn_hud)
+        app.add_systems(OnEnter(GameState::Playing), rebuild_hud)
             .add_systems(OnExit(GameState::Playing), despawn_hud)
             .add_systems(
                 Update,
                 (
-                    update_hour_indicator,
-                    update_seasion_indicator,
-                    update_time_advance_indicator,
+                    rebuild_hud.run_if(resource_changed::<CurrentLevel>),
+                    (
+                        update_clock,
+                        update_seasion_wheel,
+                        update_axis_legend,
+                        update_move_counter,
+                        update_goal_indicator,
+                        apply_hud_verbosity,
+                    ),
                 )
+                    .chain()
                     .run_if(in_state(GameState::Playing)),
             );
     }
@@ -23,19 +46,86 @@ impl Plugin for HudPlugin {
 #[derive(Component, Default)]
 pub struct Hud;
 
+/// Parts of the HUD that only show at [`HudVerbosity::Full`].
 #[derive(Component, Default)]
-pub struct LevelName;
+pub struct HudDetail;
 
+/// The face of the clock, coloured by the light outside.
 #[derive(Component, Default)]
-pub struct HourIndicator;
+pub struct ClockDial;
+
+/// Turns once a day around the middle of the [`ClockDial`], pointing up at
+/// noon and down at midnight.
+#[derive(Component, Default)]
+pub struct ClockHand;
+
+/// The day and time under the clock.
+#[derive(Component, Default)]
+pub struct ClockReadout;
+
+/// One of the seasons on the season wheel.
+#[derive(Component)]
+pub struct SeasionMarker(Seasion);
 
 #[derive(Component, Default)]
 pub struct SeasonIndicator;
 
+/// The two directions of movement that run the clock. `sense` is 1 for the
+/// one that runs it forwards by the step, -1 for the other.
+#[derive(Component)]
+pub struct AxisArrow {
+    sense: i32,
+}
+
+/// How far the clock moves for a step the way of the [`AxisArrow`] with the
+/// same `sense`.
+#[derive(Component)]
+pub struct AxisDelta {
+    sense: i32,
+}
+
+/// Shown instead of the arrows while time is frozen.
+#[derive(Component, Default)]
+pub struct FrozenIndicator;
+
+#[derive(Component, Default)]
+pub struct MoveCounter;
+
 #[derive(Component, Default)]
-pub struct TimeAdvanceIndicator;
+pub struct GoalIndicator;
+
+fn seasion_color(seasion: Seasion) -> Color {
+    match seasion {
+        Seasion::Spring => Color::srgb(0.45, 0.8, 0.4),
+        Seasion::Summer => Color::srgb(1.0, 0.85, 0.3),
+        Seasion::Autum => Color::srgb(0.9, 0.5, 0.2),
+        Seasion::Winter => Color::srgb(0.75, 0.9, 1.0),
+    }
+}
+
+fn text(value: impl Into<String>, font_size: f32) -> TextBundle {
+    TextBundle::from_section(
+        value,
+        TextStyle {
+            font_size,
+            color: Color::WHITE,
+            ..default()
+        },
+    )
+}
+
+/// Spawns the HUD for the level that is loaded, replacing the one for the
+/// level before.
+fn rebuild_hud(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    current_level: Res<CurrentLevel>,
+    huds: Query<Entity, With<Hud>>,
+) {
+    for hud in &huds {
+        commands.entity(hud).despawn_recursive();
+    }
 
-fn spawn_hud(mut commands: Commands) {
     let container = NodeBundle {
         style: Style {
             width: Val::Percent(100.0),
@@ -53,106 +143,365 @@ fn spawn_hud(mut commands: Commands) {
         hud.spawn(NodeBundle {
             style: Style {
                 width: Val::Percent(100.0),
-                height: Val::Px(100.0),
+                height: Val::Px(110.0),
                 flex_direction: FlexDirection::Row,
                 justify_content: JustifyContent::SpaceBetween,
                 padding: UiRect {
                     left: Val::Px(30.0),
                     right: Val::Px(30.0),
-                    top: Val::Px(0.0),
+                    top: Val::Px(10.0),
                     bottom: Val::Px(0.0),
                 },
-                align_items: AlignItems::Center,
+                align_items: AlignItems::FlexStart,
                 ..default()
             },
             ..default()
         })
         .with_children(|top_area| {
-            let level_name = TextBundle::from_section(
-                "level 1",
-                TextStyle {
-                    font_size: 40.0,
-                    color: Color::WHITE,
-                    ..default()
-                },
-            );
+            spawn_clock(top_area);
 
-            let hour_indicator = TextBundle::from_section(
-                "1:00",
-                TextStyle {
-                    font_size: 20.0,
-                    color: Color::WHITE,
+            top_area
+                .spawn(NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Column,
+                        align_items: AlignItems::Center,
+                        ..default()
+                    },
                     ..default()
-                },
-            );
+                })
+                .with_children(|level_info| {
+                    level_info.spawn(text(current_level.display_name(), 40.0));
+                    if let Some(description) = &current_level.description {
+                        level_info.spawn((
+                            text(description.clone(), 18.0).with_text_justify(JustifyText::Center),
+                            HudDetail,
+                        ));
+                    }
+                });
 
-            let seasion_indicator = TextBundle::from_section(
-                "winter",
-                TextStyle {
-                    font_size: 20.0,
-                    color: Color::WHITE,
-                    ..default()
-                },
-            );
-
-            top_area.spawn((hour_indicator, HourIndicator));
-            top_area.spawn((level_name, LevelName));
-            top_area.spawn((seasion_indicator, SeasonIndicator));
+            spawn_seasion_wheel(top_area);
         });
 
+        // BOTTOM Area
         hud.spawn(NodeBundle {
             style: Style {
                 width: Val::Percent(100.0),
                 height: Val::Px(100.0),
-                flex_direction: FlexDirection::Row,
+                flex_direction: FlexDirection::Column,
                 align_items: AlignItems::Center,
                 justify_content: JustifyContent::Center,
+                row_gap: Val::Px(6.0),
                 ..default()
             },
             ..default()
         })
         .with_children(|bottom_area| {
-            let time_advance = TextBundle::from_section(
-                "",
-                TextStyle {
-                    font_size: 20.0,
-                    color: Color::WHITE,
-                    ..default()
-                },
-            );
-            bottom_area.spawn((time_advance, TimeAdvanceIndicator));
+            bottom_area
+                .spawn((
+                    NodeBundle {
+                        style: Style {
+                            flex_direction: FlexDirection::Row,
+                            align_items: AlignItems::Center,
+                            column_gap: Val::Px(24.0),
+                            ..default()
+                        },
+                        ..default()
+                    },
+                    HudDetail,
+                ))
+                .with_children(|legend| {
+                    spawn_axis_legend(legend, &asset_server);
+                    legend.spawn((text("", 20.0), MoveCounter));
+                });
+            bottom_area.spawn((text("", 20.0), GoalIndicator, HudDetail));
         });
     });
 }
 
+fn spawn_clock(parent: &mut ChildBuilder) {
+    parent
+        .spawn(NodeBundle {
+            style: Style {
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                row_gap: Val::Px(4.0),
+                ..default()
+            },
+            ..default()
+        })
+        .with_children(|clock| {
+            clock
+                .spawn((
+                    NodeBundle {
+                        style: Style {
+                            width: Val::Px(56.0),
+                            height: Val::Px(56.0),
+                            border: UiRect::all(Val::Px(2.0)),
+                            ..default()
+                        },
+                        border_color: Color::WHITE.into(),
+                        border_radius: BorderRadius::MAX,
+                        ..default()
+                    },
+                    ClockDial,
+                ))
+                .with_children(|dial| {
+                    // The hand hangs from the top of a square the size of the
+                    // dial, so turning the square turns the hand about the
+                    // middle of the dial.
+                    dial.spawn((
+                        NodeBundle {
+                            style: Style {
+                                position_type: PositionType::Absolute,
+                                width: Val::Percent(100.0),
+                                height: Val::Percent(100.0),
+                                justify_content: JustifyContent::Center,
+                                ..default()
+                            },
+                            ..default()
+                        },
+                        ClockHand,
+                    ))
+                    .with_children(|hand| {
+                        hand.spawn(NodeBundle {
+                            style: Style {
+                                width: Val::Px(3.0),
+                                height: Val::Percent(50.0),
+                                ..default()
+                            },
+                            background_color: Color::WHITE.into(),
+                            ..default()
+                        });
+                    });
+                });
+
+            clock.spawn((text("", 18.0), ClockReadout));
+        });
+}
+
+fn spawn_seasion_wheel(parent: &mut ChildBuilder) {
+    parent
+        .spawn(NodeBundle {
+            style: Style {
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                row_gap: Val::Px(4.0),
+                ..default()
+            },
+            ..default()
+        })
+        .with_children(|seasions| {
+            // Laid out two by two, running clockwise from the top left.
+            seasions
+                .spawn(NodeBundle {
+                    style: Style {
+                        display: Display::Grid,
+                        grid_template_columns: RepeatedGridTrack::px(2, 24.0),
+                        grid_template_rows: RepeatedGridTrack::px(2, 24.0),
+                        row_gap: Val::Px(4.0),
+                        column_gap: Val::Px(4.0),
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|wheel| {
+                    for (seasion, row, column) in [
+                        (Seasion::Spring, 1, 1),
+                        (Seasion::Summer, 1, 2),
+                        (Seasion::Autum, 2, 2),
+                        (Seasion::Winter, 2, 1),
+                    ] {
+                        wheel.spawn((
+                            NodeBundle {
+                                style: Style {
+                                    grid_row: GridPlacement::start(row),
+                                    grid_column: GridPlacement::start(column),
+                                    ..default()
+                                },
+                                background_color: seasion_color(seasion).into(),
+                                border_radius: BorderRadius::MAX,
+                                ..default()
+                            },
+                            SeasionMarker(seasion),
+                        ));
+                    }
+                });
+
+            seasions.spawn((text("", 18.0), SeasonIndicator));
+        });
+}
+
+fn spawn_axis_legend(parent: &mut ChildBuilder, asset_server: &AssetServer) {
+    let arrow = asset_server.load("ui/arrow.png");
+    for sense in [-1, 1] {
+        parent
+            .spawn(NodeBundle {
+                style: Style {
+                    flex_direction: FlexDirection::Row,
+                    align_items: AlignItems::Center,
+                    column_gap: Val::Px(4.0),
+                    ..default()
+                },
+                ..default()
+            })
+            .with_children(|legend| {
+                legend.spawn((
+                    ImageBundle {
+                        style: Style {
+                            width: Val::Px(24.0),
+                            height: Val::Px(24.0),
+                            ..default()
+                        },
+                        image: UiImage::new(arrow.clone()),
+                        ..default()
+                    },
+                    AxisArrow { sense },
+                ));
+                legend.spawn((text("", 20.0), AxisDelta { sense }));
+            });
+    }
+    parent.spawn((text("Time is frozen", 20.0), FrozenIndicator));
+}
+
 pub fn despawn_hud(mut commands: Commands, query: Query<Entity, With<Hud>>) {
-    let hud = commands.entity(query.single());
-    hud.despawn_recursive();
+    let Ok(hud) = query.get_single() else {
+        return;
+    };
+    commands.entity(hud).despawn_recursive();
 }
 
-pub fn update_seasion_indicator(
+pub fn update_clock(
     time_state: Res<TimeState>,
+    mut dials: Query<&mut BackgroundColor, With<ClockDial>>,
+    mut hands: Query<&mut Transform, With<ClockHand>>,
+    mut readouts: Query<&mut Text, With<ClockReadout>>,
+) {
+    for mut dial in &mut dials {
+        dial.0 = NIGHT_SKY.mix(&DAY_SKY, time_state.daylight());
+    }
+
+    let minute_of_day = time_state.time.rem_euclid(24 * 60);
+    let angle = (minute_of_day as f32 / (24.0 * 60.0) - 0.5) * TAU;
+    for mut hand in &mut hands {
+        hand.rotation = Quat::from_rotation_z(angle);
+    }
+
+    for mut readout in &mut readouts {
+        readout.sections[0].value = format!(
+            "Day {} {:02}:{:02}",
+            time_state.day(),
+            minute_of_day / 60,
+            minute_of_day % 60
+        );
+    }
+}
+
+pub fn update_seasion_wheel(
+    time_state: Res<TimeState>,
+    mut markers: Query<(&SeasionMarker, &mut BackgroundColor)>,
     mut seasion_indicator: Query<&mut Text, With<SeasonIndicator>>,
 ) {
-    let mut seasion_indicator = seasion_indicator.single_mut();
-    seasion_indicator.sections[0].value = format!("{}", time_state.current_seasion());
+    let current = time_state.current_seasion();
+    for (SeasionMarker(seasion), mut background) in &mut markers {
+        let alpha = if *seasion == current {
+            1.0
+        } else {
+            OTHER_SEASION_ALPHA
+        };
+        background.0 = seasion_color(*seasion).with_alpha(alpha);
+    }
+
+    for mut text in &mut seasion_indicator {
+        text.sections[0].value = current.to_string();
+    }
 }
 
-pub fn update_hour_indicator(
+/// Points the arrows along the axis that runs the clock, each labelled with
+/// how far a step that way moves it.
+pub fn update_axis_legend(
     time_state: Res<TimeState>,
-    mut hour_indictor: Query<&mut Text, With<HourIndicator>>,
+    mut arrows: Query<(&AxisArrow, &mut Transform, &Parent)>,
+    mut deltas: Query<(&AxisDelta, &mut Text)>,
+    mut legends: Query<&mut Style, Without<FrozenIndicator>>,
+    mut frozen_indicators: Query<&mut Style, With<FrozenIndicator>>,
 ) {
-    let mut hour_indicator = hour_indictor.single_mut();
-    hour_indicator.sections[0].value = format!("{}:00", time_state.current_hour());
+    let frozen = time_state.time_axis == TimeAxis::None;
+    // UI space runs downwards, so turning clockwise is a positive angle.
+    let axis_angle = match time_state.time_axis {
+        TimeAxis::Vertical => -FRAC_PI_2,
+        TimeAxis::Horizontal | TimeAxis::None => 0.0,
+    };
+
+    for (arrow, mut transform, parent) in &mut arrows {
+        let angle = if arrow.sense > 0 {
+            axis_angle
+        } else {
+            axis_angle + PI
+        };
+        transform.rotation = Quat::from_rotation_z(angle);
+
+        if let Ok(mut style) = legends.get_mut(parent.get()) {
+            style.display = if frozen { Display::None } else { Display::Flex };
+        }
+    }
+
+    for (delta, mut text) in &mut deltas {
+        text.sections[0].value = format!("{:+}", delta.sense * time_state.time_step_delta);
+    }
+
+    for mut style in &mut frozen_indicators {
+        style.display = if frozen { Display::Flex } else { Display::None };
+    }
 }
 
-pub fn update_time_advance_indicator(
+pub fn update_move_counter(
+    stats: Res<LevelStats>,
+    mut move_counter: Query<&mut Text, With<MoveCounter>>,
+) {
+    for mut text in &mut move_counter {
+        text.sections[0].value = format!("Moves: {}", stats.moves);
+    }
+}
+
+/// Lists whatever the clock still has to satisfy before the goal lets the
+/// player through.
+pub fn update_goal_indicator(
     time_state: Res<TimeState>,
-    mut time_advance_indicator: Query<&mut Text, With<TimeAdvanceIndicator>>,
+    current_level: Res<CurrentLevel>,
+    mut goal_indicator: Query<&mut Text, With<GoalIndicator>>,
+) {
+    let unmet: Vec<String> = current_level
+        .goals
+        .iter()
+        .flat_map(|goal| goal.unmet(&time_state))
+        .map(|requirement| requirement.to_string())
+        .collect();
+
+    for mut text in &mut goal_indicator {
+        text.sections[0].value = if unmet.is_empty() {
+            String::new()
+        } else {
+            format!("To finish: {}", unmet.join(", "))
+        };
+    }
+}
+
+/// Hides the whole HUD, or just the details, as the player's preferences ask.
+pub fn apply_hud_verbosity(
+    preferences: Res<Preferences>,
+    mut hud: Query<&mut Visibility, With<Hud>>,
+    mut details: Query<&mut Visibility, (With<HudDetail>, Without<Hud>)>,
 ) {
-    let mut time_advance_indicator = time_advance_indicator.single_mut();
-    time_advance_indicator.sections[0].value = format!(
-        "Time will move {} hours when you move in the {} direction",
-        time_state.time_step_delta, time_state.time_axis
-    );
+    let (hud_visibility, details_visibility) = match preferences.hud_verbosity {
+        HudVerbosity::Full => (Visibility::Inherited, Visibility::Inherited),
+        HudVerbosity::Compact => (Visibility::Inherited, Visibility::Hidden),
+        HudVerbosity::Hidden => (Visibility::Hidden, Visibility::Hidden),
+    };
+
+    for mut visibility in &mut hud {
+        visibility.set_if_neq(hud_visibility);
+    }
+    for mut visibility in &mut details {
+        visibility.set_if_neq(details_visibility);
+    }
 }
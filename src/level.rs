@@ -0,0 +1,118 @@
+use bevy::prelude::*;
+use bevy_ecs_ldtk::prelude::*;
+use cycle_game::sim::{self, LevelState, OrbRegistry};
+
+use crate::game_state::{GameState, TimeState};
+
+pub struct LevelPlugin;
+
+impl Plugin for LevelPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CurrentLevel>()
+            .init_resource::<LevelState>()
+            .init_resource::<LevelStart>()
+            .init_resource::<RestartPending>()
+            .add_event::<RestartLevel>()
+            .add_event::<QuitToTitle>()
+            .add_systems(
+                Update,
+                (restart_level, quit_to_title, cache_current_level).chain(),
+            );
+    }
+}
+
+/// The rules for the level that is currently spawned. These are rebuilt from
+/// the raw LDtk level whenever a level spawns.
+#[derive(Default, Resource, Debug, Deref)]
+pub struct CurrentLevel(sim::Level);
+
+/// The clock the current level started with, which restarts go back to.
+#[derive(Default, Resource, Debug, Deref)]
+pub struct LevelStart(pub TimeState);
+
+/// Sent to put the current level back the way it spawned, clock included.
+#[derive(Event, Default)]
+pub struct RestartLevel;
+
+/// Sent to drop the level being played and go back to the title screen. The
+/// world is spawned afresh when play starts again.
+#[derive(Event, Default)]
+pub struct QuitToTitle;
+
+/// Set between asking for a restart and the level spawning again. The clock
+/// is only reset once the old player is gone, so nothing reacts to the new
+/// time at the old position.
+#[derive(Resource, Default)]
+pub struct RestartPending(bool);
+
+fn restart_level(
+    mut commands: Commands,
+    mut restarts: EventReader<RestartLevel>,
+    mut restart_pending: ResMut<RestartPending>,
+    ldtk_worlds: Query<Entity, With<Handle<LdtkProject>>>,
+) {
+    if restarts.read().count() == 0 {
+        return;
+    }
+
+    for ldtk_world in &ldtk_worlds {
+        commands.entity(ldtk_world).insert(Respawn);
+    }
+    restart_pending.0 = true;
+}
+
+fn quit_to_title(
+    mut commands: Commands,
+    mut quits: EventReader<QuitToTitle>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut time_state: ResMut<TimeState>,
+    ldtk_worlds: Query<Entity, With<Handle<LdtkProject>>>,
+) {
+    if quits.read().count() == 0 {
+        return;
+    }
+
+    for ldtk_world in &ldtk_worlds {
+        commands.entity(ldtk_world).despawn_recursive();
+    }
+    *time_state = TimeState::default();
+    next_state.set(GameState::WelcomeScreen);
+}
+
+/// Rebuilds [`CurrentLevel`] and sets the clock up for the level that just
+/// spawned, following its [`sim::TimeOnEnter`] unless it is being restarted.
+pub fn cache_current_level(
+    mut current_level: ResMut<CurrentLevel>,
+    mut level_start: ResMut<LevelStart>,
+    mut restart_pending: ResMut<RestartPending>,
+    mut time_state: ResMut<TimeState>,
+    mut level_state: ResMut<LevelState>,
+    mut level_events: EventReader<LevelEvent>,
+    ldtk_project_entities: Query<&Handle<LdtkProject>>,
+    ldtk_project_assets: Res<Assets<LdtkProject>>,
+    orb_registry: Res<OrbRegistry>,
+) {
+    for level_event in level_events.read() {
+        if let LevelEvent::Spawned(level_iid) = level_event {
+            let ldtk_project = ldtk_project_assets
+                .get(ldtk_project_entities.single())
+                .expect("LdtkProject should be loaded when level is spawned");
+            let level = ldtk_project
+                .get_raw_level_by_iid(level_iid.get())
+                .expect("spawned level should exist in project");
+
+            *current_level = CurrentLevel(
+                sim::Level::from_ldtk(level, &orb_registry)
+                    .expect("spawned level should be playable"),
+            );
+
+            if restart_pending.0 {
+                restart_pending.0 = false;
+            } else {
+                *level_start = LevelStart(current_level.time_on_enter.apply(*time_state));
+            }
+            *time_state = **level_start;
+            *level_state = current_level.initial_state();
+        }
+    }
+}
@@ -0,0 +1,202 @@
+use bevy::prelude::*;
+use bevy_ecs_ldtk::prelude::*;
+use cycle_game::bindings::{Action, Bindings};
+
+use crate::{
+    actions::{self, ActionState},
+    game_state::{GameState, TimeState},
+    level::{CurrentLevel, LevelStart, QuitToTitle},
+};
+
+pub struct LevelCompletePlugin;
+
+impl Plugin for LevelCompletePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<LevelStats>()
+            .add_systems(Update, reset_stats_on_level_spawn)
+            .add_systems(
+                OnEnter(GameState::LevelComplete),
+                spawn_level_complete_screen,
+            )
+            .add_systems(OnExit(GameState::LevelComplete), despawn_screen)
+            .add_systems(OnEnter(GameState::Victory), spawn_victory_screen)
+            .add_systems(OnExit(GameState::Victory), despawn_screen)
+            .add_systems(
+                Update,
+                (
+                    level_complete_key_press.run_if(in_state(GameState::LevelComplete)),
+                    victory_key_press.run_if(in_state(GameState::Victory)),
+                ),
+            );
+    }
+}
+
+/// How the current attempt at a level is going. Undoing a move takes it off
+/// the move count, restarting the level starts it over.
+#[derive(Resource, Debug, Default)]
+pub struct LevelStats {
+    pub moves: usize,
+}
+
+/// Marks the level complete and victory screens.
+#[derive(Component)]
+pub struct CompletionScreen;
+
+fn reset_stats_on_level_spawn(
+    mut stats: ResMut<LevelStats>,
+    mut level_events: EventReader<LevelEvent>,
+) {
+    for level_event in level_events.read() {
+        if let LevelEvent::Spawned(_) = level_event {
+            *stats = LevelStats::default();
+        }
+    }
+}
+
+/// Confirm moves on to the next level, or to the victory screen after the last
+/// one in the project.
+fn level_complete_key_press(
+    mut next_state: ResMut<NextState<GameState>>,
+    mut level_selection: ResMut<LevelSelection>,
+    ldtk_project_entities: Query<&Handle<LdtkProject>>,
+    ldtk_project_assets: Res<Assets<LdtkProject>>,
+    actions: Res<ActionState>,
+) {
+    if !actions.just_pressed(Action::Confirm) {
+        return;
+    }
+
+    let LevelSelection::Indices(indices) = *level_selection else {
+        panic!("level selection should always be Indices in this game");
+    };
+    let next = LevelIndices {
+        level: indices.level + 1,
+        ..indices
+    };
+
+    let ldtk_project = ldtk_project_assets
+        .get(ldtk_project_entities.single())
+        .expect("LdtkProject should be loaded when a level is complete");
+
+    if ldtk_project.get_raw_level_at_indices(&next).is_some() {
+        *level_selection = LevelSelection::Indices(next);
+        next_state.set(GameState::Playing);
+    } else {
+        next_state.set(GameState::Victory);
+    }
+}
+
+/// Confirm goes back to the title screen, ready to start from the first level.
+fn victory_key_press(
+    mut quits: EventWriter<QuitToTitle>,
+    mut level_selection: ResMut<LevelSelection>,
+    actions: Res<ActionState>,
+) {
+    if actions.just_pressed(Action::Confirm) {
+        *level_selection = LevelSelection::index(0);
+        quits.send_default();
+    }
+}
+
+fn spawn_level_complete_screen(
+    commands: Commands,
+    stats: Res<LevelStats>,
+    time_state: Res<TimeState>,
+    level_start: Res<LevelStart>,
+    current_level: Res<CurrentLevel>,
+    bindings: Res<Bindings>,
+) {
+    spawn_screen(
+        commands,
+        &format!("{} complete", current_level.display_name()),
+        &[
+            format!("{} moves", stats.moves),
+            format!(
+                "{} minutes on the clock",
+                time_state.minutes_since(&level_start)
+            ),
+            format!(
+                "arrived at {}:00 in {}",
+                time_state.current_hour(),
+                time_state.current_seasion()
+            ),
+        ],
+        &format!(
+            "press {} to continue",
+            actions::describe(&bindings, Action::Confirm)
+        ),
+    );
+}
+
+fn spawn_victory_screen(commands: Commands, bindings: Res<Bindings>) {
+    spawn_screen(
+        commands,
+        "You win!",
+        &["every level is complete".to_string()],
+        &format!(
+            "press {} to return to the menu",
+            actions::describe(&bindings, Action::Confirm)
+        ),
+    );
+}
+
+fn spawn_screen(mut commands: Commands, title: &str, lines: &[String], prompt: &str) {
+    let container = NodeBundle {
+        style: Style {
+            width: Val::Percent(100.0),
+            height: Val::Percent(100.0),
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            flex_direction: FlexDirection::Column,
+            ..default()
+        },
+        ..default()
+    };
+
+    commands
+        .spawn((container, CompletionScreen))
+        .with_children(|screen| {
+            screen.spawn(
+                TextBundle::from_section(
+                    title,
+                    TextStyle {
+                        font_size: 100.0,
+                        color: Color::WHITE,
+                        ..default()
+                    },
+                )
+                .with_text_justify(JustifyText::Center),
+            );
+
+            for line in lines {
+                screen.spawn(
+                    TextBundle::from_section(
+                        line.clone(),
+                        TextStyle {
+                            font_size: 40.0,
+                            color: Color::WHITE,
+                            ..default()
+                        },
+                    )
+                    .with_text_justify(JustifyText::Center),
+                );
+            }
+
+            screen.spawn(
+                TextBundle::from_section(
+                    prompt,
+                    TextStyle {
+                        font_size: 30.0,
+                        color: Color::WHITE,
+                        ..default()
+                    },
+                )
+                .with_text_justify(JustifyText::Center),
+            );
+        });
+}
+
+fn despawn_screen(mut commands: Commands, query: Query<Entity, With<CompletionScreen>>) {
+    let screen = commands.entity(query.single());
+    screen.despawn_recursive();
+}
@@ -0,0 +1,10 @@
+//! The parts of Cycle Game that don't need a running Bevy `App`.
+//!
+//! The game binary builds its plugins on top of these modules, and tools and
+//! tests can use them directly.
+
+pub mod bindings;
+pub mod consts;
+pub mod save;
+pub mod sim;
+pub mod storage;
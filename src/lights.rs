@@ -1,30 +1,36 @@
-use crate::{consts, orbs::Orb, player::Player};
+use crate::{consts, level::CurrentLevel, orbs::Orb, player::Player};
 use bevy::{color::palettes::css::WHITE, prelude::*};
+use bevy_ecs_ldtk::GridCoords;
 use bevy_light_2d::light::{AmbientLight2d, PointLight2d, PointLight2dBundle};
+use cycle_game::sim::LevelState;
 
-use crate::game_state::{GameState, TimeState};
+use crate::game_state::{PlayState, TimeState};
 
 pub struct LightPlugin;
 
 #[derive(Component)]
 struct OrbLight;
 
+const ORB_LIGHT_INTENSITY: f32 = 25.0;
+
 impl Plugin for LightPlugin {
     fn build(&self, app: &mut App) {
         app.add_systems(
             Update,
-            (update_daylight, add_orb_lights, add_player_light)
-                .run_if(in_state(GameState::Playing)),
+            (
+                update_daylight,
+                add_orb_lights,
+                add_player_light,
+                update_orb_lights,
+            )
+                .run_if(in_state(PlayState::Running)),
         );
     }
 }
 
 fn update_daylight(time_state: Res<TimeState>, mut ambient_light: Query<&mut AmbientLight2d>) {
     for mut light in &mut ambient_light {
-        let hour = time_state.current_hour();
-        let daylight = consts::BASE_LIGHT
-            + 0.8 * 0.5 * (((hour as f32 - 12.0) * (2.0 * 3.141) / 24.0).cos() + 1.0);
-        light.brightness = daylight;
+        light.brightness = consts::BASE_LIGHT + 0.8 * time_state.daylight();
     }
 }
 
@@ -49,14 +55,16 @@ fn add_player_light(
     }
 }
 
+/// Orb lights start out white, [`update_orb_lights`] colours them once the
+/// level's rules are known.
 fn add_orb_lights(mut commands: Commands, query: Query<Entity, (With<Orb>, Without<OrbLight>)>) {
     for orb in &query {
         let light = commands
             .spawn((PointLight2dBundle {
                 point_light: PointLight2d {
                     radius: 90.0,
-                    color: Color::Srgba(WHITE),
-                    intensity: 25.0,
+                    color: Color::WHITE,
+                    intensity: ORB_LIGHT_INTENSITY,
                     falloff: 30.0,
                 },
                 ..default()
@@ -66,3 +74,33 @@ fn add_orb_lights(mut commands: Commands, query: Query<Entity, (With<Orb>, Witho
         commands.entity(orb).insert(OrbLight);
     }
 }
+
+/// Colours orb lights after their effect, and turns off the lights of orbs
+/// that have used up their charges, and back on once they recharge.
+fn update_orb_lights(
+    current_level: Res<CurrentLevel>,
+    level_state: Res<LevelState>,
+    orbs: Query<(&GridCoords, &Children), (With<Orb>, With<OrbLight>)>,
+    mut lights: Query<&mut PointLight2d>,
+) {
+    for (grid_coords, children) in &orbs {
+        let color = current_level
+            .orbs_at(grid_coords)
+            .next()
+            .map_or(Color::WHITE, |orb| orb.effect.light_color());
+        let intensity = if current_level.orb_spent(&level_state, grid_coords) {
+            0.0
+        } else {
+            ORB_LIGHT_INTENSITY
+        };
+        let mut lights = lights.iter_many_mut(children);
+        while let Some(mut light) = lights.fetch_next() {
+            if light.intensity != intensity {
+                light.intensity = intensity;
+            }
+            if light.color != color {
+                light.color = color;
+            }
+        }
+    }
+}
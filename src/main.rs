@@ -4,32 +4,68 @@
 // Feel free to delete this line.
 #![allow(clippy::too_many_arguments, clippy::type_complexity)]
 
-mod consts;
+mod actions;
+mod audio;
+mod blocks;
+mod controls_screen;
+mod death;
 mod debug;
+mod echoes;
 mod game_state;
+mod gates;
 mod goal;
+mod hostiles;
 mod hud;
+mod level;
+mod level_complete;
 mod lights;
 mod orbs;
+mod pause;
+mod plants;
 mod player;
+mod preferences;
+mod progress;
+mod replay;
+mod terrain;
+mod time_preview;
+mod undo;
 mod walls;
 mod welcome_screen;
 
+use actions::ActionsPlugin;
+use audio::SoundPlugin;
 use bevy::{asset::AssetMetaCheck, prelude::*};
 use bevy_ecs_ldtk::prelude::*;
 use bevy_light_2d::prelude::*;
+use blocks::BlockPlugin;
+use controls_screen::ControlsScreenPlugin;
+use cycle_game::consts;
+use death::DeathPlugin;
 use debug::DebugPlugin;
-use game_state::{GameState, GameStatePlugin, TimeState};
+use echoes::EchoPlugin;
+use game_state::{GameState, GameStatePlugin};
+use gates::GatePlugin;
 use goal::GoalPlugin;
+use hostiles::HostilePlugin;
 use hud::HudPlugin;
+use level::LevelPlugin;
+use level_complete::LevelCompletePlugin;
 use lights::LightPlugin;
 use orbs::OrbsPlugin;
+use pause::PausePlugin;
+use plants::PlantPlugin;
+use preferences::PreferencesPlugin;
 use welcome_screen::WelcomeScreenPlugin;
 // use hud::HudPlugin;
 use player::{Player, PlayerPlugin};
+use progress::ProgressPlugin;
+use replay::ReplayPlugin;
+use terrain::TerrainPlugin;
+use time_preview::TimePreviewPlugin;
+use undo::UndoPlugin;
 use walls::WallPlugin;
 
-fn startup(mut commands: Commands, asset_server: Res<AssetServer>) {
+fn startup(mut commands: Commands) {
     let mut camera = Camera2dBundle::default();
     camera.projection.scale = 1.0;
     camera.transform.translation.x += 900.0 / 4.0;
@@ -44,7 +80,17 @@ fn startup(mut commands: Commands, asset_server: Res<AssetServer>) {
     ));
 }
 
-fn start_game(mut commands: Commands, asset_server: Res<AssetServer>) {
+/// Spawns the LDtk world, unless it is still around from before, as it is
+/// when coming back from the death screen.
+fn start_game(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    ldtk_worlds: Query<(), With<Handle<LdtkProject>>>,
+) {
+    if !ldtk_worlds.is_empty() {
+        return;
+    }
+
     commands.spawn(LdtkWorldBundle {
         ldtk_handle: asset_server.load("tile-based-game.ldtk"),
         ..Default::default()
@@ -73,24 +119,38 @@ fn main() {
         )
         .add_plugins(Light2dPlugin)
         .add_plugins(GameStatePlugin)
+        .add_plugins(ActionsPlugin)
         .add_plugins(LdtkPlugin)
+        .add_plugins(LevelPlugin)
         .add_plugins(PlayerPlugin)
+        .add_plugins(UndoPlugin)
         .add_plugins(WallPlugin)
+        .add_plugins(GatePlugin)
+        .add_plugins(TerrainPlugin)
         .add_plugins(OrbsPlugin)
         .add_plugins(GoalPlugin)
+        .add_plugins(HostilePlugin)
+        .add_plugins(DeathPlugin)
+        .add_plugins(LevelCompletePlugin)
+        .add_plugins(ProgressPlugin)
+        .add_plugins(ReplayPlugin)
         .add_plugins(DebugPlugin)
         .add_plugins(WelcomeScreenPlugin)
+        .add_plugins(ControlsScreenPlugin)
+        .add_plugins(PausePlugin)
+        .add_plugins(PreferencesPlugin)
         .add_plugins(LightPlugin)
         .add_plugins(HudPlugin)
+        .add_plugins(TimePreviewPlugin)
+        .add_plugins(EchoPlugin)
+        .add_plugins(BlockPlugin)
+        .add_plugins(PlantPlugin)
+        .add_plugins(SoundPlugin)
         .add_systems(Startup, startup)
         .add_systems(OnEnter(GameState::Playing), start_game)
         .add_systems(
             Update,
-            (
-                // translate_grid_coords_entities,
-                camera_fit_inside_current_level,
-            )
-                .run_if(in_state(GameState::Playing)),
+            camera_fit_inside_current_level.run_if(in_state(GameState::Playing)),
         )
         .insert_resource(LevelSelection::index(0))
         .run();
@@ -159,15 +219,3 @@ pub fn camera_fit_inside_current_level(
         }
     }
 }
-
-fn translate_grid_coords_entities(
-    mut grid_coords_entities: Query<(&mut Transform, &GridCoords), Changed<GridCoords>>,
-) {
-    for (mut transform, grid_coords) in grid_coords_entities.iter_mut() {
-        transform.translation = bevy_ecs_ldtk::utils::grid_coords_to_translation(
-            *grid_coords,
-            IVec2::splat(consts::GRID_SIZE),
-        )
-        .extend(transform.translation.z);
-    }
-}
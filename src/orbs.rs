@@ -1,10 +1,15 @@
 use bevy::prelude::*;
 use bevy_ecs_ldtk::prelude::*;
+use cycle_game::sim::{LevelState, OrbRegistry};
 
-use crate::game_state::GameState;
+use crate::level::CurrentLevel;
+
+use crate::game_state::PlayState;
 
 pub struct OrbsPlugin;
 
+const SPENT_ORB_COLOR: Color = Color::srgba(0.4, 0.4, 0.4, 0.6);
+
 #[derive(Component, Default)]
 pub struct Orb;
 
@@ -17,57 +22,10 @@ impl Default for OrbTimer {
     }
 }
 
-#[derive(Component, Default)]
-pub struct AxisSwitch;
-
+/// Every kind of orb spawns the same way, what it does lives in the
+/// [`OrbRegistry`].
 #[derive(Default, Bundle, LdtkEntity)]
-struct AxisSwitchBundle {
-    axis_switch: AxisSwitch,
-    orb: Orb,
-    #[sprite_sheet_bundle]
-    sprite_sheet_bundle: LdtkSpriteSheetBundle,
-    #[grid_coords]
-    grid_coords: GridCoords,
-    animation_timer: OrbTimer,
-    anim_frame: OrbAnimFrame,
-}
-
-#[derive(Component, Default)]
-pub struct DirectionSwitch;
-
-#[derive(Default, Bundle, LdtkEntity)]
-struct DirectionSwitchBundle {
-    direction_switch: DirectionSwitch,
-    orb: Orb,
-    #[sprite_sheet_bundle]
-    sprite_sheet_bundle: LdtkSpriteSheetBundle,
-    #[grid_coords]
-    grid_coords: GridCoords,
-    animation_timer: OrbTimer,
-    anim_frame: OrbAnimFrame,
-}
-
-#[derive(Component, Default)]
-pub struct SpeedUp;
-
-#[derive(Default, Bundle, LdtkEntity)]
-struct SpeedUpBundle {
-    speed_up: SpeedUp,
-    orb: Orb,
-    #[sprite_sheet_bundle]
-    sprite_sheet_bundle: LdtkSpriteSheetBundle,
-    #[grid_coords]
-    grid_coords: GridCoords,
-    animation_timer: OrbTimer,
-    anim_frame: OrbAnimFrame,
-}
-
-#[derive(Component, Default)]
-pub struct SlowDown;
-
-#[derive(Default, Bundle, LdtkEntity)]
-struct SlowDownBundle {
-    speed_up: SpeedUp,
+struct OrbBundle {
     orb: Orb,
     #[sprite_sheet_bundle]
     sprite_sheet_bundle: LdtkSpriteSheetBundle,
@@ -89,16 +47,20 @@ impl OrbAnimFrame {
     }
 }
 
-#[derive(Component)]
-pub struct PlayerIn;
-
 impl Plugin for OrbsPlugin {
     fn build(&self, app: &mut App) {
-        app.register_ldtk_entity::<AxisSwitchBundle>("Axis_switch")
-            .register_ldtk_entity::<DirectionSwitchBundle>("Direction_switch")
-            .register_ldtk_entity::<SpeedUpBundle>("Speed_up")
-            .register_ldtk_entity::<SlowDownBundle>("Slow_down")
-            .add_systems(Update, (animate_orbs).run_if(in_state(GameState::Playing)));
+        app.init_resource::<OrbRegistry>().add_systems(
+            Update,
+            (animate_orbs, dim_spent_orbs).run_if(in_state(PlayState::Running)),
+        );
+    }
+
+    /// Runs once every plugin has had the chance to register its own orbs.
+    fn finish(&self, app: &mut App) {
+        let orb_registry = app.world().resource::<OrbRegistry>().clone();
+        for identifier in orb_registry.identifiers() {
+            app.register_ldtk_entity::<OrbBundle>(identifier);
+        }
     }
 }
 
@@ -119,3 +81,21 @@ fn animate_orbs(
         }
     }
 }
+
+/// Greys out orbs that have used up their charges.
+fn dim_spent_orbs(
+    current_level: Res<CurrentLevel>,
+    level_state: Res<LevelState>,
+    mut orbs: Query<(&GridCoords, &mut Sprite), With<Orb>>,
+) {
+    for (grid_coords, mut sprite) in &mut orbs {
+        let color = if current_level.orb_spent(&level_state, grid_coords) {
+            SPENT_ORB_COLOR
+        } else {
+            Color::WHITE
+        };
+        if sprite.color != color {
+            sprite.color = color;
+        }
+    }
+}
@@ -0,0 +1,326 @@
+use bevy::prelude::*;
+use cycle_game::{
+    bindings::Action,
+    save::{HudVerbosity, Preferences, ScreenMode},
+};
+
+use crate::{
+    actions::ActionState,
+    game_state::{PauseMenu, PlayState},
+    level::{QuitToTitle, RestartLevel},
+};
+
+/// Dims the game behind the pause menu and the screens it opens.
+pub const OVERLAY_BACKGROUND: Color = Color::srgba(0.0, 0.0, 0.0, 0.75);
+
+const SELECTED_COLOR: Color = Color::srgb(1.0, 0.85, 0.2);
+
+/// How much one press changes the volume by.
+const VOLUME_STEP: f32 = 0.1;
+
+pub struct PausePlugin;
+
+impl Plugin for PausePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<MenuCursor>()
+            .add_systems(
+                Update,
+                pause_from_input.run_if(in_state(PlayState::Running)),
+            )
+            .add_systems(OnEnter(PauseMenu::Main), spawn_main_menu)
+            .add_systems(OnExit(PauseMenu::Main), despawn_menu)
+            .add_systems(OnEnter(PauseMenu::Settings), spawn_settings_menu)
+            .add_systems(OnExit(PauseMenu::Settings), despawn_menu)
+            .add_systems(
+                Update,
+                (
+                    main_menu_input.run_if(in_state(PauseMenu::Main)),
+                    settings_menu_input.run_if(in_state(PauseMenu::Settings)),
+                    update_menu_rows,
+                )
+                    .chain()
+                    .run_if(in_state(PlayState::Paused)),
+            );
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+enum MainItem {
+    Resume,
+    RestartLevel,
+    Settings,
+    QuitToTitle,
+}
+
+impl MainItem {
+    const ALL: [MainItem; 4] = [
+        MainItem::Resume,
+        MainItem::RestartLevel,
+        MainItem::Settings,
+        MainItem::QuitToTitle,
+    ];
+
+    fn label(self) -> &'static str {
+        match self {
+            MainItem::Resume => "Resume",
+            MainItem::RestartLevel => "Restart level",
+            MainItem::Settings => "Settings",
+            MainItem::QuitToTitle => "Quit to title",
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+enum SettingsItem {
+    Volume,
+    MusicVolume,
+    SfxVolume,
+    ScreenMode,
+    HudVerbosity,
+    KeyBindings,
+    Back,
+}
+
+impl SettingsItem {
+    const ALL: [SettingsItem; 7] = [
+        SettingsItem::Volume,
+        SettingsItem::MusicVolume,
+        SettingsItem::SfxVolume,
+        SettingsItem::ScreenMode,
+        SettingsItem::HudVerbosity,
+        SettingsItem::KeyBindings,
+        SettingsItem::Back,
+    ];
+
+    fn label(self, preferences: &Preferences) -> String {
+        match self {
+            SettingsItem::Volume => format!("Volume: < {:.0}% >", preferences.volume * 100.0),
+            SettingsItem::MusicVolume => {
+                format!("Music: < {:.0}% >", preferences.music_volume * 100.0)
+            }
+            SettingsItem::SfxVolume => {
+                format!("Sound effects: < {:.0}% >", preferences.sfx_volume * 100.0)
+            }
+            SettingsItem::ScreenMode => format!("Window: < {:?} >", preferences.screen_mode),
+            SettingsItem::HudVerbosity => format!("HUD: < {:?} >", preferences.hud_verbosity),
+            SettingsItem::KeyBindings => "Key bindings".to_string(),
+            SettingsItem::Back => "Back".to_string(),
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct PauseMenuScreen;
+
+/// One line of the menu being shown, by position.
+#[derive(Component)]
+pub struct MenuRow(usize);
+
+/// The selected line of the menu being shown.
+#[derive(Resource, Default)]
+struct MenuCursor(usize);
+
+impl MenuCursor {
+    fn navigate(&mut self, actions: &ActionState, rows: usize) {
+        if actions.just_pressed(Action::MoveUp) {
+            self.0 = self.0.checked_sub(1).unwrap_or(rows - 1);
+        } else if actions.just_pressed(Action::MoveDown) {
+            self.0 = (self.0 + 1) % rows;
+        }
+    }
+}
+
+fn pause_from_input(mut next_play_state: ResMut<NextState<PlayState>>, actions: Res<ActionState>) {
+    if actions.just_pressed(Action::Pause) {
+        next_play_state.set(PlayState::Paused);
+    }
+}
+
+fn main_menu_input(
+    mut cursor: ResMut<MenuCursor>,
+    mut next_play_state: ResMut<NextState<PlayState>>,
+    mut next_pause_menu: ResMut<NextState<PauseMenu>>,
+    mut restarts: EventWriter<RestartLevel>,
+    mut quits: EventWriter<QuitToTitle>,
+    actions: Res<ActionState>,
+) {
+    if actions.just_pressed(Action::Pause) || actions.just_pressed(Action::Cancel) {
+        next_play_state.set(PlayState::Running);
+        return;
+    }
+
+    cursor.navigate(&actions, MainItem::ALL.len());
+    if !actions.just_pressed(Action::Confirm) {
+        return;
+    }
+
+    match MainItem::ALL[cursor.0] {
+        MainItem::Resume => next_play_state.set(PlayState::Running),
+        MainItem::RestartLevel => {
+            restarts.send_default();
+            next_play_state.set(PlayState::Running);
+        }
+        MainItem::Settings => next_pause_menu.set(PauseMenu::Settings),
+        MainItem::QuitToTitle => {
+            quits.send_default();
+        }
+    }
+}
+
+/// Left and right change the selected option. Changes apply straight away.
+fn settings_menu_input(
+    mut cursor: ResMut<MenuCursor>,
+    mut next_pause_menu: ResMut<NextState<PauseMenu>>,
+    mut preferences: ResMut<Preferences>,
+    actions: Res<ActionState>,
+) {
+    if actions.just_pressed(Action::Cancel) {
+        next_pause_menu.set(PauseMenu::Main);
+        return;
+    }
+
+    cursor.navigate(&actions, SettingsItem::ALL.len());
+    let item = SettingsItem::ALL[cursor.0];
+
+    let change = if actions.just_pressed(Action::MoveLeft) {
+        -1
+    } else if actions.just_pressed(Action::MoveRight) {
+        1
+    } else {
+        0
+    };
+    if change != 0 {
+        match item {
+            SettingsItem::Volume => step_volume(&mut preferences.volume, change),
+            SettingsItem::MusicVolume => step_volume(&mut preferences.music_volume, change),
+            SettingsItem::SfxVolume => step_volume(&mut preferences.sfx_volume, change),
+            SettingsItem::ScreenMode => {
+                preferences.screen_mode = cycle(&ScreenMode::ALL, preferences.screen_mode, change);
+            }
+            SettingsItem::HudVerbosity => {
+                preferences.hud_verbosity =
+                    cycle(&HudVerbosity::ALL, preferences.hud_verbosity, change);
+            }
+            SettingsItem::KeyBindings | SettingsItem::Back => {}
+        }
+    }
+
+    if actions.just_pressed(Action::Confirm) {
+        match item {
+            SettingsItem::KeyBindings => next_pause_menu.set(PauseMenu::Controls),
+            SettingsItem::Back => next_pause_menu.set(PauseMenu::Main),
+            _ => {}
+        }
+    }
+}
+
+fn step_volume(volume: &mut f32, change: i32) {
+    // Rounding stops repeated steps drifting away from whole percentages.
+    *volume = ((*volume + change as f32 * VOLUME_STEP) * 100.0)
+        .round()
+        .clamp(0.0, 100.0)
+        / 100.0;
+}
+
+/// The option `change` places along from `current`, wrapping at the ends.
+fn cycle<T: PartialEq + Copy>(options: &[T], current: T, change: i32) -> T {
+    let index = options
+        .iter()
+        .position(|option| *option == current)
+        .unwrap_or(0) as i32;
+    options[(index + change).rem_euclid(options.len() as i32) as usize]
+}
+
+fn update_menu_rows(
+    cursor: Res<MenuCursor>,
+    preferences: Res<Preferences>,
+    pause_menu: Option<Res<State<PauseMenu>>>,
+    mut rows: Query<(&MenuRow, &mut Text)>,
+) {
+    let Some(pause_menu) = pause_menu else {
+        return;
+    };
+
+    for (MenuRow(index), mut text) in &mut rows {
+        let section = &mut text.sections[0];
+        section.style.color = if *index == cursor.0 {
+            SELECTED_COLOR
+        } else {
+            Color::WHITE
+        };
+        if *pause_menu.get() == PauseMenu::Settings {
+            section.value = SettingsItem::ALL[*index].label(&preferences);
+        }
+    }
+}
+
+fn spawn_main_menu(commands: Commands, cursor: ResMut<MenuCursor>) {
+    let labels = MainItem::ALL.map(|item| item.label().to_string());
+    spawn_menu(commands, cursor, "Paused", &labels);
+}
+
+fn spawn_settings_menu(
+    commands: Commands,
+    cursor: ResMut<MenuCursor>,
+    preferences: Res<Preferences>,
+) {
+    let labels = SettingsItem::ALL.map(|item| item.label(&preferences));
+    spawn_menu(commands, cursor, "Settings", &labels);
+}
+
+fn spawn_menu(
+    mut commands: Commands,
+    mut cursor: ResMut<MenuCursor>,
+    title: &str,
+    labels: &[String],
+) {
+    cursor.0 = 0;
+
+    let container = NodeBundle {
+        style: Style {
+            width: Val::Percent(100.0),
+            height: Val::Percent(100.0),
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            flex_direction: FlexDirection::Column,
+            row_gap: Val::Px(10.0),
+            ..default()
+        },
+        background_color: OVERLAY_BACKGROUND.into(),
+        ..default()
+    };
+
+    commands
+        .spawn((container, PauseMenuScreen))
+        .with_children(|menu| {
+            menu.spawn(TextBundle::from_section(
+                title,
+                TextStyle {
+                    font_size: 75.0,
+                    color: Color::WHITE,
+                    ..default()
+                },
+            ));
+
+            for (index, label) in labels.iter().enumerate() {
+                menu.spawn((
+                    TextBundle::from_section(
+                        label.clone(),
+                        TextStyle {
+                            font_size: 40.0,
+                            color: Color::WHITE,
+                            ..default()
+                        },
+                    ),
+                    MenuRow(index),
+                ));
+            }
+        });
+}
+
+fn despawn_menu(mut commands: Commands, query: Query<Entity, With<PauseMenuScreen>>) {
+    let Ok(menu) = query.get_single() else {
+        return;
+    };
+    commands.entity(menu).despawn_recursive();
+}
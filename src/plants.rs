@@ -0,0 +1,61 @@
+use bevy::prelude::*;
+use bevy_ecs_ldtk::prelude::*;
+use cycle_game::sim::{identifiers, PlantStage};
+
+use crate::{
+    game_state::{GameState, TimeState},
+    level::CurrentLevel,
+};
+
+/// The tile in the `Environment` tileset drawn for each stage of growth.
+fn stage_tile(stage: PlantStage) -> usize {
+    match stage {
+        PlantStage::Seedling => 367,
+        PlantStage::Sapling => 369,
+        PlantStage::Tree => 198,
+        PlantStage::Bare => 182,
+    }
+}
+
+#[derive(Default, Component)]
+pub struct Plant;
+
+#[derive(Default, Bundle, LdtkEntity)]
+struct PlantBundle {
+    plant: Plant,
+    #[sprite_sheet_bundle]
+    sprite_sheet_bundle: LdtkSpriteSheetBundle,
+    #[grid_coords]
+    grid_coords: GridCoords,
+}
+
+pub struct PlantPlugin;
+
+impl Plugin for PlantPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_ldtk_entity::<PlantBundle>(identifiers::PLANT)
+            .add_systems(Update, grow_plants.run_if(in_state(GameState::Playing)));
+    }
+}
+
+/// Shows each plant at the stage the clock puts it at.
+fn grow_plants(
+    time_state: Res<TimeState>,
+    current_level: Res<CurrentLevel>,
+    mut plants: Query<(&EntityIid, &mut TextureAtlas), With<Plant>>,
+) {
+    for (iid, mut atlas) in &mut plants {
+        let Some(plant) = current_level
+            .plants
+            .iter()
+            .find(|plant| plant.iid == iid.as_str())
+        else {
+            continue;
+        };
+
+        let tile = stage_tile(plant.stage(&time_state));
+        if atlas.index != tile {
+            atlas.index = tile;
+        }
+    }
+}
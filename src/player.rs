@@ -1,12 +1,19 @@
-use bevy::{a11y::accesskit::Vec2, prelude::*, render::render_resource::AsBindGroupShaderType};
+use bevy::{ecs::query::QueryData, prelude::*};
 use bevy_ecs_ldtk::prelude::*;
+use cycle_game::{
+    bindings::Action,
+    consts,
+    sim::{self, identifiers, Direction, Input, LevelState, MovementMode, Step, StepOutcome},
+};
 
 use crate::{
-    consts,
-    game_state::{GameState, TimeAxis, TimeState},
-    goal::Goal,
-    orbs::{AxisSwitch, DirectionSwitch, Orb, SlowDown, SpeedUp},
-    walls::LevelWalls,
+    actions::ActionState,
+    audio::SoundEffect,
+    game_state::{GameState, PlayState, TimeState},
+    level::CurrentLevel,
+    level_complete::LevelStats,
+    replay::{playback_inactive, Recording},
+    undo::MoveHistory,
 };
 
 #[derive(Component, Default)]
@@ -20,6 +27,7 @@ struct PlayerBundle {
     #[grid_coords]
     grid_coords: GridCoords,
     animation_timer: PlayerAnimationTimer,
+    buffered_move: BufferedMove,
 }
 
 pub struct PlayerPlugin;
@@ -33,228 +41,310 @@ impl Default for PlayerAnimationTimer {
     }
 }
 
+/// The player's preferred way of moving, used unless the level asks for a
+/// particular [`MovementMode`].
+#[derive(Resource, Default, Debug)]
+pub struct MovementSettings {
+    pub mode: MovementMode,
+}
+
+impl MovementSettings {
+    /// How the player moves in `level`.
+    pub fn mode_for(&self, level: &sim::Level) -> MovementMode {
+        level.movement_mode.unwrap_or(self.mode)
+    }
+}
+
 impl Plugin for PlayerPlugin {
     fn build(&self, app: &mut bevy::prelude::App) {
-        app.register_ldtk_entity::<PlayerBundle>("Player")
-            .add_systems(
-                Update,
-                (move_player_from_input, animate_player, check_goal_acheived)
-                    .run_if(in_state(GameState::Playing)),
-            )
+        app.register_ldtk_entity::<PlayerBundle>(identifiers::PLAYER)
+            .init_resource::<MovementSettings>()
             .add_systems(
                 Update,
                 (
-                    check_switch_orb_hit,
-                    check_direction_orb_hit,
-                    check_slow_down_orb_hit,
-                    check_speed_up_orb_hit,
-                    check_in_no_orb,
-                    check_in_orb,
+                    move_player_from_input
+                        .run_if(movement_mode_is(MovementMode::Free).and_then(playback_inactive)),
+                    tween_tiles,
+                    move_player_on_grid
+                        .after(tween_tiles)
+                        .run_if(movement_mode_is(MovementMode::Grid).and_then(playback_inactive)),
+                    toggle_movement_mode,
+                    animate_player,
+                    check_goal_acheived,
                 )
-                    .chain()
-                    .run_if(in_state(GameState::Playing)),
+                    .run_if(in_state(PlayState::Running)),
             );
     }
 }
 
-pub enum Facing {
-    Up,
-    Down,
-    Left,
-    Right,
+fn movement_mode_is(
+    mode: MovementMode,
+) -> impl Fn(Res<MovementSettings>, Res<CurrentLevel>) -> bool {
+    move |settings, current_level| settings.mode_for(&current_level) == mode
 }
 
-/// Basic player movement system
-fn move_player_from_input(
-    mut players: Query<(&mut Transform, &mut GridCoords, &mut Sprite), With<Player>>,
-    mut time_state: ResMut<TimeState>,
-    input: Res<ButtonInput<KeyCode>>,
-    level_walls: Res<LevelWalls>,
-) {
-    let (movement, facing, axis, sense) = if input.pressed(KeyCode::KeyW) {
-        ((0.0, 1.0), Facing::Up, TimeAxis::Vertical, 1)
-    } else if input.pressed(KeyCode::KeyA) {
-        ((-1.0, 0.0), Facing::Left, TimeAxis::Horizontal, -1)
-    } else if input.pressed(KeyCode::KeyS) {
-        ((0.0, -1.0), Facing::Down, TimeAxis::Vertical, -1)
-    } else if input.pressed(KeyCode::KeyD) {
-        ((1.0, 0.0), Facing::Right, TimeAxis::Horizontal, 1)
-    } else {
-        return;
-    };
+fn toggle_movement_mode(mut settings: ResMut<MovementSettings>, actions: Res<ActionState>) {
+    if actions.just_pressed(Action::ToggleMovement) {
+        settings.mode = match settings.mode {
+            MovementMode::Free => MovementMode::Grid,
+            MovementMode::Grid => MovementMode::Free,
+        };
+    }
+}
 
-    // TODO break this out into another system
-
-    for (mut transform, mut player_grid_coords, mut sprite) in players.iter_mut() {
-        let translation = Vec2::from(movement) * consts::MOVEMENT_SPEED;
-        let new_transform = transform.with_translation(
-            transform.translation
-                + Vec3 {
-                    x: translation.x as f32,
-                    y: translation.y as f32,
-                    z: 0.0,
-                },
-        );
+pub fn face(sprite: &mut Sprite, direction: Direction) {
+    match direction {
+        Direction::Left => sprite.flip_x = true,
+        Direction::Right => sprite.flip_x = false,
+        _ => {}
+    }
+}
 
-        let new_grid_coords = bevy_ecs_ldtk::utils::translation_to_grid_coords(
-            new_transform.translation.xy(),
-            IVec2 {
-                x: consts::GRID_SIZE,
-                y: consts::GRID_SIZE,
-            },
-        );
+/// The components that mirror a [`sim::Player`].
+#[derive(QueryData)]
+#[query_data(mutable)]
+pub struct SimPlayer {
+    entity: Entity,
+    transform: &'static mut Transform,
+    grid_coords: &'static mut GridCoords,
+    in_orb: Has<PlayerInOrb>,
+}
 
-        if !level_walls.in_wall(&new_grid_coords) {
-            *player_grid_coords = new_grid_coords;
-            *transform = new_transform;
-            if time_state.time_axis == axis {
-                time_state.time = time_state.time + sense * time_state.time_step_delta
-            }
+impl SimPlayerItem<'_> {
+    pub fn to_sim(&self) -> sim::Player {
+        sim::Player {
+            translation: self.transform.translation.xy(),
+            grid_coords: *self.grid_coords,
+            in_orb: self.in_orb,
         }
+    }
 
-        match facing {
-            Facing::Left => sprite.flip_x = true,
-            Facing::Right => sprite.flip_x = false,
-            _ => {}
+    /// Copies `player` back onto the entity, cancelling any tween in progress.
+    pub fn apply(&mut self, commands: &mut Commands, player: &sim::Player) {
+        self.transform.translation = player.translation.extend(self.transform.translation.z);
+        commands.entity(self.entity).remove::<TileTween>();
+        self.apply_state(commands, player);
+    }
+
+    /// Copies everything but the translation of `player` onto the entity and
+    /// slides the sprite over to it, taking longer if the move was slowed.
+    pub fn apply_tweened(
+        &mut self,
+        commands: &mut Commands,
+        player: &sim::Player,
+        outcome: &StepOutcome,
+    ) {
+        let mut tween = TileTween::new(self.transform.translation.xy(), player.translation);
+        if outcome.slowed {
+            tween.timer = Timer::from_seconds(
+                consts::TILE_TWEEN_SECONDS / consts::SAPLING_SPEED_FACTOR as f32,
+                TimerMode::Once,
+            );
         }
+        commands.entity(self.entity).insert(tween);
+        self.apply_state(commands, player);
     }
-}
 
-fn animate_player(
-    time: Res<Time>,
-    mut query: Query<(&mut TextureAtlas, &mut PlayerAnimationTimer), With<Player>>,
-) {
-    for (mut atlas, mut timer) in &mut query {
-        timer.tick(time.delta());
-        if (timer.just_finished()) {
-            atlas.index = atlas.index + 1;
-            if atlas.index > 143 + 5 {
-                atlas.index = 143
+    /// Grid coordinates are only touched when they change so
+    /// `Changed<GridCoords>` filters keep working.
+    fn apply_state(&mut self, commands: &mut Commands, player: &sim::Player) {
+        if *self.grid_coords != player.grid_coords {
+            *self.grid_coords = player.grid_coords;
+        }
+
+        match (self.in_orb, player.in_orb) {
+            (false, true) => {
+                commands.entity(self.entity).insert(PlayerInOrb);
+            }
+            (true, false) => {
+                commands.entity(self.entity).remove::<PlayerInOrb>();
             }
+            _ => {}
         }
     }
 }
 
-// Switch orb!
-fn check_switch_orb_hit(
+/// Basic player movement system
+fn move_player_from_input(
+    mut commands: Commands,
+    mut players: Query<(SimPlayer, &mut Sprite), With<Player>>,
     mut time_state: ResMut<TimeState>,
-    players: Query<&GridCoords, (With<Player>, Without<PlayerInOrb>, Changed<GridCoords>)>,
-    orbs: Query<&GridCoords, With<AxisSwitch>>,
+    mut level_state: ResMut<LevelState>,
+    mut history: ResMut<MoveHistory>,
+    mut stats: ResMut<LevelStats>,
+    mut recording: ResMut<Recording>,
+    mut sound_effects: EventWriter<SoundEffect>,
+    actions: Res<ActionState>,
+    current_level: Res<CurrentLevel>,
 ) {
-    for player in &players {
-        for orb in &orbs {
-            if player == orb {
-                let new_axis = match time_state.time_axis {
-                    TimeAxis::Horizontal => TimeAxis::Vertical,
-                    TimeAxis::Vertical => TimeAxis::Horizontal,
-                    TimeAxis::None => TimeAxis::None,
-                };
-                time_state.time_axis = new_axis;
-                return;
+    let Some(direction) = actions.pressed_direction() else {
+        return;
+    };
+
+    // Each key press is one move as far as undo is concerned.
+    let new_move = actions.just_pressed_direction().is_some();
+
+    for (mut sim_player, mut sprite) in players.iter_mut() {
+        let mut player = sim_player.to_sim();
+        let input = Input::Step {
+            step: Step::Free(direction),
+            new_move,
+        };
+
+        let outcome = input.apply(
+            &current_level,
+            &mut history,
+            &mut player,
+            &mut time_state,
+            &mut level_state,
+        );
+        sound_effects.send_batch(SoundEffect::for_step(&outcome));
+
+        if outcome.moved {
+            if new_move {
+                stats.moves += 1;
             }
+            recording.push(input);
+            sim_player.apply(&mut commands, &player);
         }
+
+        face(&mut sprite, direction);
     }
 }
 
+/// The last direction pressed while the player was still sliding between
+/// tiles, taken as the next move once the slide finishes.
+#[derive(Component, Default)]
+pub struct BufferedMove(Option<Direction>);
+
+/// Slides a sprite from one tile to the next.
 #[derive(Component)]
-pub struct PlayerInOrb;
+pub struct TileTween {
+    from: Vec2,
+    to: Vec2,
+    timer: Timer,
+}
 
-pub fn check_in_orb(
-    mut commands: Commands,
-    players: Query<
-        (Entity, &GridCoords),
-        (With<Player>, Without<PlayerInOrb>, Changed<GridCoords>),
-    >,
-    orbs: Query<&GridCoords, With<Orb>>,
-) {
-    for (player, player_coords) in &players {
-        for orb_coords in &orbs {
-            if player_coords == orb_coords {
-                commands.entity(player).insert(PlayerInOrb);
-                return;
-            }
+impl TileTween {
+    pub fn new(from: Vec2, to: Vec2) -> Self {
+        Self {
+            from,
+            to,
+            timer: Timer::from_seconds(consts::TILE_TWEEN_SECONDS, TimerMode::Once),
         }
     }
 }
 
-pub fn check_in_no_orb(
+fn tween_tiles(
     mut commands: Commands,
-    players: Query<(Entity, &GridCoords), (With<Player>, With<PlayerInOrb>, Changed<GridCoords>)>,
-    orbs: Query<&GridCoords, With<Orb>>,
+    time: Res<Time>,
+    mut tweens: Query<(Entity, &mut Transform, &mut TileTween)>,
 ) {
-    for (player, player_coords) in &players {
-        for orb_coords in &orbs {
-            if player_coords == orb_coords {
-                return;
-            }
+    for (entity, mut transform, mut tween) in &mut tweens {
+        tween.timer.tick(time.delta());
+        transform.translation = tween
+            .from
+            .lerp(tween.to, tween.timer.fraction())
+            .extend(transform.translation.z);
+
+        if tween.timer.finished() {
+            commands.entity(entity).remove::<TileTween>();
         }
-        commands.entity(player).remove::<PlayerInOrb>();
     }
 }
-// Direction orb!
-fn check_direction_orb_hit(
+
+/// Grid movement: each key press commits a move of exactly one tile. The
+/// rules run as soon as the move is committed, the sprite catches up after.
+fn move_player_on_grid(
+    mut commands: Commands,
+    mut players: Query<(SimPlayer, &mut Sprite, &mut BufferedMove, Has<TileTween>), With<Player>>,
     mut time_state: ResMut<TimeState>,
-    players: Query<&GridCoords, (With<Player>, Without<PlayerInOrb>, Changed<GridCoords>)>,
-    orbs: Query<&GridCoords, With<DirectionSwitch>>,
+    mut level_state: ResMut<LevelState>,
+    mut history: ResMut<MoveHistory>,
+    mut stats: ResMut<LevelStats>,
+    mut recording: ResMut<Recording>,
+    mut sound_effects: EventWriter<SoundEffect>,
+    actions: Res<ActionState>,
+    current_level: Res<CurrentLevel>,
 ) {
-    for player in &players {
-        for orb in &orbs {
-            if player == orb {
-                time_state.time_step_delta = -time_state.time_step_delta;
-                return;
-            }
+    for (mut sim_player, mut sprite, mut buffered_move, tweening) in players.iter_mut() {
+        if let Some(direction) = actions.just_pressed_direction() {
+            buffered_move.0 = Some(direction);
         }
-    }
-}
 
-// Speed up orb!
-fn check_speed_up_orb_hit(
-    mut time_state: ResMut<TimeState>,
-    players: Query<&GridCoords, (With<Player>, Without<PlayerInOrb>, Changed<GridCoords>)>,
-    orbs: Query<&GridCoords, With<SpeedUp>>,
-) {
-    for player in &players {
-        for orb in &orbs {
-            if player == orb {
-                time_state.time_step_delta = time_state.time_step_delta + 1;
-                return;
-            }
+        if tweening {
+            continue;
+        }
+
+        // Holding a key keeps walking once the buffer is empty.
+        let Some(direction) = buffered_move
+            .0
+            .take()
+            .or_else(|| actions.pressed_direction())
+        else {
+            continue;
+        };
+
+        let mut player = sim_player.to_sim();
+        let input = Input::Step {
+            step: Step::Tile(direction),
+            new_move: true,
+        };
+
+        let outcome = input.apply(
+            &current_level,
+            &mut history,
+            &mut player,
+            &mut time_state,
+            &mut level_state,
+        );
+        sound_effects.send_batch(SoundEffect::for_step(&outcome));
+
+        if outcome.moved {
+            stats.moves += 1;
+            recording.push(input);
+            sim_player.apply_tweened(&mut commands, &player, &outcome);
         }
+
+        face(&mut sprite, direction);
     }
 }
 
-// Slow down orb!
-fn check_slow_down_orb_hit(
-    mut time_state: ResMut<TimeState>,
-    players: Query<&GridCoords, (With<Player>, Without<PlayerInOrb>, Changed<GridCoords>)>,
-    orbs: Query<&GridCoords, With<SlowDown>>,
+fn animate_player(
+    time: Res<Time>,
+    mut query: Query<(&mut TextureAtlas, &mut PlayerAnimationTimer), With<Player>>,
 ) {
-    for player in &players {
-        for orb in &orbs {
-            if player == orb {
-                time_state.time_step_delta = time_state.time_step_delta - 1;
-                return;
+    for (mut atlas, mut timer) in &mut query {
+        timer.tick(time.delta());
+        if timer.just_finished() {
+            atlas.index += 1;
+            if atlas.index > 143 + 5 {
+                atlas.index = 143
             }
         }
     }
 }
 
+/// Marks a player standing on an orb, mirroring [`sim::Player::in_orb`].
+#[derive(Component)]
+pub struct PlayerInOrb;
+
 /// Did the player reach the goal?
 fn check_goal_acheived(
-    level_selection: ResMut<LevelSelection>,
-    players: Query<&GridCoords, (With<Player>, Changed<GridCoords>)>,
-    goals: Query<&GridCoords, With<Goal>>,
+    mut next_state: ResMut<NextState<GameState>>,
+    players: Query<Ref<GridCoords>, With<Player>>,
+    time_state: Res<TimeState>,
+    current_level: Res<CurrentLevel>,
 ) {
-    if players
-        .iter()
-        .zip(goals.iter())
-        .any(|(player_grid_coords, goal_grid_coords)| player_grid_coords == goal_grid_coords)
-    {
-        let indices = match level_selection.into_inner() {
-            LevelSelection::Indices(indices) => indices,
-            _ => panic!("level selection should always be Indices in this game"),
-        };
-
-        indices.level += 1;
+    // A goal can start accepting the player while they stand on it, since
+    // free movement runs the clock without leaving the tile.
+    if players.iter().any(|player_grid_coords| {
+        (player_grid_coords.is_changed() || time_state.is_changed())
+            && current_level.goal_reached(&player_grid_coords, &time_state)
+            && current_level
+                .death_cause(&player_grid_coords, &time_state)
+                .is_none()
+    }) {
+        next_state.set(GameState::LevelComplete);
     }
 }
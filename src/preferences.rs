@@ -0,0 +1,38 @@
+use bevy::{
+    audio::Volume,
+    prelude::*,
+    window::{PrimaryWindow, WindowMode},
+};
+use cycle_game::save::{Preferences, ScreenMode};
+
+pub struct PreferencesPlugin;
+
+impl Plugin for PreferencesPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Preferences>().add_systems(
+            Update,
+            apply_preferences.run_if(resource_changed::<Preferences>),
+        );
+    }
+}
+
+/// Puts the volume and window mode into effect as soon as they change. The
+/// HUD follows its own setting when it updates.
+fn apply_preferences(
+    preferences: Res<Preferences>,
+    mut global_volume: ResMut<GlobalVolume>,
+    mut windows: Query<&mut Window, With<PrimaryWindow>>,
+) {
+    global_volume.volume = Volume::new(preferences.volume);
+
+    let mode = match preferences.screen_mode {
+        ScreenMode::Windowed => WindowMode::Windowed,
+        ScreenMode::Borderless => WindowMode::BorderlessFullscreen,
+        ScreenMode::Fullscreen => WindowMode::Fullscreen,
+    };
+    for mut window in &mut windows {
+        if window.mode != mode {
+            window.mode = mode;
+        }
+    }
+}
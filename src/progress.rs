@@ -0,0 +1,136 @@
+use bevy::prelude::*;
+use bevy_ecs_ldtk::prelude::*;
+use cycle_game::{
+    bindings::Bindings,
+    save::{Preferences, SaveData},
+};
+
+use crate::{
+    game_state::{GameState, TimeState},
+    level::{CurrentLevel, LevelStart},
+    level_complete::LevelStats,
+    player::MovementSettings,
+};
+
+pub struct ProgressPlugin;
+
+impl Plugin for ProgressPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, load_save)
+            .add_systems(OnEnter(GameState::LevelComplete), record_level_complete)
+            .add_systems(
+                Update,
+                (
+                    clamp_level_selection,
+                    save_settings.run_if(
+                        resource_changed::<MovementSettings>
+                            .or_else(resource_changed::<Bindings>)
+                            .or_else(resource_changed::<Preferences>),
+                    ),
+                ),
+            );
+    }
+}
+
+/// The player's progress and settings, written back whenever they change.
+#[derive(Resource, Debug, Default, Deref, DerefMut)]
+pub struct Progress(SaveData);
+
+impl Progress {
+    fn store(&self) {
+        if let Err(error) = self.0.store() {
+            warn!("couldn't save progress: {error}");
+        }
+    }
+}
+
+/// Picks up where the player left off, on the furthest level they unlocked.
+fn load_save(
+    mut commands: Commands,
+    mut movement_settings: ResMut<MovementSettings>,
+    mut level_selection: ResMut<LevelSelection>,
+) {
+    let save = SaveData::load().unwrap_or_else(|error| {
+        warn!("starting without saved progress: {error}");
+        SaveData::default()
+    });
+
+    movement_settings.mode = save.settings.movement_mode;
+    commands.insert_resource(save.settings.bindings.clone());
+    commands.insert_resource(save.settings.preferences);
+    *level_selection = LevelSelection::index(save.unlocked_levels.saturating_sub(1));
+    commands.insert_resource(Progress(save));
+}
+
+/// A save can unlock more levels than the project has, if levels were taken
+/// out since it was written. Once the project loads, a selection past its
+/// last level falls back to the last one.
+fn clamp_level_selection(
+    mut level_selection: ResMut<LevelSelection>,
+    mut project_events: EventReader<AssetEvent<LdtkProject>>,
+    ldtk_project_assets: Res<Assets<LdtkProject>>,
+) {
+    for project_event in project_events.read() {
+        let AssetEvent::LoadedWithDependencies { id } = project_event else {
+            continue;
+        };
+        let Some(ldtk_project) = ldtk_project_assets.get(*id) else {
+            continue;
+        };
+        let level_count = ldtk_project.json_data().levels.len();
+        if let LevelSelection::Indices(indices) = *level_selection {
+            if indices.level >= level_count {
+                *level_selection = LevelSelection::index(level_count.saturating_sub(1));
+            }
+        }
+    }
+}
+
+fn record_level_complete(
+    mut progress: ResMut<Progress>,
+    stats: Res<LevelStats>,
+    time_state: Res<TimeState>,
+    level_start: Res<LevelStart>,
+    current_level: Res<CurrentLevel>,
+    level_selection: Res<LevelSelection>,
+    ldtk_project_entities: Query<&Handle<LdtkProject>>,
+    ldtk_project_assets: Res<Assets<LdtkProject>>,
+) {
+    progress
+        .levels
+        .entry(current_level.identifier.clone())
+        .or_default()
+        .update(stats.moves, time_state.minutes_since(&level_start));
+
+    if let LevelSelection::Indices(indices) = *level_selection {
+        let next = LevelIndices {
+            level: indices.level + 1,
+            ..indices
+        };
+        let ldtk_project = ldtk_project_assets
+            .get(ldtk_project_entities.single())
+            .expect("LdtkProject should be loaded when a level is complete");
+        if ldtk_project.get_raw_level_at_indices(&next).is_some() {
+            progress.unlock(next.level);
+        }
+    }
+
+    progress.store();
+}
+
+fn save_settings(
+    mut progress: ResMut<Progress>,
+    movement_settings: Res<MovementSettings>,
+    bindings: Res<Bindings>,
+    preferences: Res<Preferences>,
+) {
+    if progress.settings.movement_mode != movement_settings.mode
+        || progress.settings.bindings != *bindings
+        || progress.settings.preferences != *preferences
+    {
+        progress.settings.movement_mode = movement_settings.mode;
+        progress.settings.bindings = bindings.clone();
+        progress.settings.preferences = *preferences;
+        progress.store();
+    }
+}
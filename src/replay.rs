@@ -0,0 +1,320 @@
+use bevy::prelude::*;
+use bevy_ecs_ldtk::prelude::*;
+use cycle_game::{
+    bindings::Action,
+    consts,
+    sim::{Input, LevelState, Replay, Step},
+    storage,
+};
+
+use crate::{
+    actions::ActionState,
+    audio::SoundEffect,
+    game_state::{GameState, PlayState, TimeState},
+    level::{cache_current_level, CurrentLevel, LevelStart, RestartLevel},
+    level_complete::LevelStats,
+    player::{face, Player, SimPlayer, TileTween},
+    undo::MoveHistory,
+};
+
+/// Where recordings are saved to and played back from.
+const REPLAY_FILE: &str = "replay.json";
+
+pub struct ReplayPlugin;
+
+impl Plugin for ReplayPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Recording>()
+            .add_systems(Update, start_recording.after(cache_current_level))
+            .add_systems(
+                Update,
+                (
+                    save_recording,
+                    start_playback.run_if(playback_inactive),
+                    (playback_controls, play_back, update_replay_indicator)
+                        .chain()
+                        .run_if(resource_exists::<Playback>),
+                )
+                    .run_if(in_state(PlayState::Running)),
+            )
+            .add_systems(OnExit(GameState::Playing), stop_playback)
+            .add_systems(
+                Update,
+                despawn_replay_indicator.run_if(resource_removed::<Playback>()),
+            );
+    }
+}
+
+/// Everything the player has done since the current level spawned.
+#[derive(Resource, Default, Debug)]
+pub struct Recording(Option<Replay>);
+
+impl Recording {
+    pub fn push(&mut self, input: Input) {
+        if let Some(replay) = &mut self.0 {
+            replay.inputs.push(input);
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+enum PlaybackSpeed {
+    Normal,
+    FastForward,
+    Paused,
+}
+
+/// A replay being played back. The player's own input is ignored until it
+/// finishes.
+#[derive(Resource, Debug)]
+pub struct Playback {
+    replay: Replay,
+    position: usize,
+    speed: PlaybackSpeed,
+    /// False until the replay's level has spawned.
+    started: bool,
+}
+
+#[derive(Component)]
+pub struct ReplayIndicator;
+
+/// Run condition for systems that take the player's input.
+pub fn playback_inactive(playback: Option<Res<Playback>>) -> bool {
+    playback.is_none()
+}
+
+fn start_recording(
+    mut recording: ResMut<Recording>,
+    mut playback: Option<ResMut<Playback>>,
+    mut time_state: ResMut<TimeState>,
+    mut level_start: ResMut<LevelStart>,
+    mut level_events: EventReader<LevelEvent>,
+    current_level: Res<CurrentLevel>,
+) {
+    for level_event in level_events.read() {
+        if let LevelEvent::Spawned(_) = level_event {
+            if let Some(playback) = playback.as_mut().filter(|playback| !playback.started) {
+                // The replay may be for another level, or have started at
+                // another time, so restarts afterwards go back to its start.
+                *level_start = LevelStart(playback.replay.start);
+                *time_state = playback.replay.start;
+                playback.started = true;
+            }
+            recording.0 = Some(Replay::new(current_level.iid.clone(), *time_state));
+        }
+    }
+}
+
+/// Saves everything done in the current level so far.
+fn save_recording(recording: Res<Recording>, actions: Res<ActionState>) {
+    if !actions.just_pressed(Action::SaveReplay) {
+        return;
+    }
+    let Some(replay) = &recording.0 else {
+        return;
+    };
+
+    match replay
+        .to_json()
+        .map_err(anyhow::Error::from)
+        .and_then(|json| Ok(storage::write(REPLAY_FILE, &json)?))
+    {
+        Ok(()) => info!(
+            "saved a replay of {} inputs to {REPLAY_FILE}",
+            replay.inputs.len()
+        ),
+        Err(error) => warn!("couldn't save the replay: {error}"),
+    }
+}
+
+/// Restarts the saved replay's level and plays the replay on it.
+fn start_playback(
+    mut commands: Commands,
+    mut level_selection: ResMut<LevelSelection>,
+    mut restarts: EventWriter<RestartLevel>,
+    ldtk_project_entities: Query<&Handle<LdtkProject>>,
+    ldtk_project_assets: Res<Assets<LdtkProject>>,
+    actions: Res<ActionState>,
+) {
+    if !actions.just_pressed(Action::PlayReplay) {
+        return;
+    }
+
+    let replay = match storage::read(REPLAY_FILE) {
+        Ok(Some(json)) => Replay::from_json(&json).map_err(anyhow::Error::from),
+        Ok(None) => Err(anyhow::anyhow!("there is no saved replay")),
+        Err(error) => Err(error.into()),
+    };
+    let replay = match replay {
+        Ok(replay) => replay,
+        Err(error) => {
+            warn!("couldn't load the replay: {error}");
+            return;
+        }
+    };
+
+    let ldtk_project = ldtk_project_assets
+        .get(ldtk_project_entities.single())
+        .expect("LdtkProject should be loaded while playing");
+    let Some((indices, _)) = ldtk_project
+        .iter_raw_levels_with_indices()
+        .find(|(_, level)| level.iid == replay.level_iid)
+    else {
+        warn!(
+            "the replay's level {} isn't in the project",
+            replay.level_iid
+        );
+        return;
+    };
+
+    *level_selection = LevelSelection::Indices(indices);
+    restarts.send_default();
+
+    commands.spawn((
+        TextBundle::from_section(
+            "",
+            TextStyle {
+                font_size: 20.0,
+                color: Color::WHITE,
+                ..default()
+            },
+        )
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            left: Val::Px(30.0),
+            bottom: Val::Px(30.0),
+            ..default()
+        }),
+        ReplayIndicator,
+    ));
+    commands.insert_resource(Playback {
+        replay,
+        position: 0,
+        speed: PlaybackSpeed::Normal,
+        started: false,
+    });
+}
+
+/// Fast-forwards, pauses or stops the replay. Stepping through a paused
+/// replay is handled by [`play_back`].
+fn playback_controls(
+    mut commands: Commands,
+    mut playback: ResMut<Playback>,
+    actions: Res<ActionState>,
+) {
+    if actions.just_pressed(Action::StopReplay) {
+        commands.remove_resource::<Playback>();
+    } else if actions.just_pressed(Action::FastForwardReplay) {
+        playback.speed = match playback.speed {
+            PlaybackSpeed::FastForward => PlaybackSpeed::Normal,
+            _ => PlaybackSpeed::FastForward,
+        };
+    } else if actions.just_pressed(Action::PauseReplay) {
+        playback.speed = match playback.speed {
+            PlaybackSpeed::Paused => PlaybackSpeed::Normal,
+            _ => PlaybackSpeed::Paused,
+        };
+    }
+}
+
+/// Feeds the replay's inputs through the same rules the player's input goes
+/// through: one per frame, or one per tile slide for grid steps.
+fn play_back(
+    mut commands: Commands,
+    mut playback: ResMut<Playback>,
+    mut players: Query<(SimPlayer, &mut Sprite, Has<TileTween>), With<Player>>,
+    mut time_state: ResMut<TimeState>,
+    mut level_state: ResMut<LevelState>,
+    mut history: ResMut<MoveHistory>,
+    mut stats: ResMut<LevelStats>,
+    mut sound_effects: EventWriter<SoundEffect>,
+    actions: Res<ActionState>,
+    current_level: Res<CurrentLevel>,
+) {
+    if !playback.started {
+        return;
+    }
+    let Ok((mut sim_player, mut sprite, tweening)) = players.get_single_mut() else {
+        return;
+    };
+
+    let inputs = match playback.speed {
+        PlaybackSpeed::Normal if tweening => 0,
+        PlaybackSpeed::Normal => 1,
+        PlaybackSpeed::FastForward => consts::REPLAY_FAST_FORWARD_INPUTS,
+        PlaybackSpeed::Paused => actions.just_pressed(Action::StepReplay) as usize,
+    };
+
+    for _ in 0..inputs {
+        let Some(&input) = playback.replay.inputs.get(playback.position) else {
+            break;
+        };
+        playback.position += 1;
+
+        let mut player = sim_player.to_sim();
+        let outcome = input.apply(
+            &current_level,
+            &mut history,
+            &mut player,
+            &mut time_state,
+            &mut level_state,
+        );
+        if let Input::Step { .. } = input {
+            sound_effects.send_batch(SoundEffect::for_step(&outcome));
+        }
+        if !outcome.moved {
+            continue;
+        }
+
+        match input {
+            Input::Step { step, new_move } => {
+                if new_move {
+                    stats.moves += 1;
+                }
+                face(&mut sprite, step.direction());
+                if matches!(step, Step::Tile(_)) && playback.speed == PlaybackSpeed::Normal {
+                    sim_player.apply_tweened(&mut commands, &player, &outcome);
+                    break;
+                }
+            }
+            Input::Undo => stats.moves = stats.moves.saturating_sub(1),
+            Input::Redo => stats.moves += 1,
+        }
+        sim_player.apply(&mut commands, &player);
+    }
+
+    if playback.position == playback.replay.inputs.len() {
+        info!("replay finished");
+        commands.remove_resource::<Playback>();
+    }
+}
+
+fn update_replay_indicator(
+    playback: Res<Playback>,
+    mut indicator: Query<&mut Text, With<ReplayIndicator>>,
+) {
+    let Ok(mut indicator) = indicator.get_single_mut() else {
+        return;
+    };
+    let speed = match playback.speed {
+        PlaybackSpeed::Normal => "",
+        PlaybackSpeed::FastForward => ", fast-forward",
+        PlaybackSpeed::Paused => ", paused",
+    };
+    indicator.sections[0].value = format!(
+        "replay {}/{}{speed}",
+        playback.position,
+        playback.replay.inputs.len()
+    );
+}
+
+/// Playback ends when the level does.
+fn stop_playback(mut commands: Commands) {
+    commands.remove_resource::<Playback>();
+}
+
+fn despawn_replay_indicator(mut commands: Commands, query: Query<Entity, With<ReplayIndicator>>) {
+    for indicator in &query {
+        commands.entity(indicator).despawn_recursive();
+    }
+}
@@ -0,0 +1,243 @@
+//! The save file: which levels are unlocked, the best results for each level
+//! and the player's settings.
+//!
+//! Saves are JSON with a `version` field. Older saves are brought up to date
+//! one version at a time by [`MIGRATIONS`] before they are read, so changing
+//! the format once it has been released means bumping [`SAVE_VERSION`] and
+//! adding a migration rather than throwing old progress away.
+
+use std::collections::BTreeMap;
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use thiserror::Error;
+
+use crate::{
+    bindings::Bindings,
+    sim::MovementMode,
+    storage::{self, StorageError},
+};
+
+const SAVE_FILE: &str = "save.json";
+
+/// The version written by this build.
+pub const SAVE_VERSION: u32 = 1;
+
+/// `MIGRATIONS[n]` turns a version `n + 1` save into a version `n + 2` one.
+const MIGRATIONS: [fn(&mut Value); SAVE_VERSION as usize - 1] = [];
+
+#[derive(Debug, Error)]
+pub enum SaveError {
+    #[error("save data is not valid JSON: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("save data has no version")]
+    MissingVersion,
+    #[error("save data is version {0}, but this build only reads up to version {SAVE_VERSION}")]
+    TooNew(u32),
+    #[error(transparent)]
+    Storage(#[from] StorageError),
+}
+
+/// The best results on a level so far. Each is kept separately, so the
+/// fewest moves and the fastest time can come from different runs.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LevelRecord {
+    pub best_moves: Option<usize>,
+    /// The fewest minutes the clock moved between the level starting and the
+    /// goal, counting time run backwards the same as time run forwards.
+    pub best_minutes: Option<u32>,
+}
+
+impl LevelRecord {
+    /// Keeps whichever results beat the ones on record.
+    pub fn update(&mut self, moves: usize, minutes: u32) {
+        self.best_moves = Some(self.best_moves.map_or(moves, |best| best.min(moves)));
+        self.best_minutes = Some(self.best_minutes.map_or(minutes, |best| best.min(minutes)));
+    }
+}
+
+#[derive(Debug, Default, PartialEq, Eq, Copy, Clone, Hash, Serialize, Deserialize)]
+pub enum ScreenMode {
+    #[default]
+    Windowed,
+    Borderless,
+    Fullscreen,
+}
+
+impl ScreenMode {
+    pub const ALL: [ScreenMode; 3] = [
+        ScreenMode::Windowed,
+        ScreenMode::Borderless,
+        ScreenMode::Fullscreen,
+    ];
+}
+
+/// How much the HUD shows while playing.
+#[derive(Debug, Default, PartialEq, Eq, Copy, Clone, Hash, Serialize, Deserialize)]
+pub enum HudVerbosity {
+    /// Everything, including the hints along the bottom.
+    #[default]
+    Full,
+    /// Just the level name, clock and season.
+    Compact,
+    Hidden,
+}
+
+impl HudVerbosity {
+    pub const ALL: [HudVerbosity; 3] = [
+        HudVerbosity::Full,
+        HudVerbosity::Compact,
+        HudVerbosity::Hidden,
+    ];
+}
+
+/// The options on the settings menu.
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Preferences {
+    /// From 0 for silent to 1 for full volume. Scales both buses.
+    pub volume: f32,
+    pub music_volume: f32,
+    pub sfx_volume: f32,
+    pub screen_mode: ScreenMode,
+    pub hud_verbosity: HudVerbosity,
+}
+
+impl Default for Preferences {
+    fn default() -> Self {
+        Self {
+            volume: 1.0,
+            music_volume: 1.0,
+            sfx_volume: 1.0,
+            screen_mode: ScreenMode::default(),
+            hud_verbosity: HudVerbosity::default(),
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub movement_mode: MovementMode,
+    pub bindings: Bindings,
+    pub preferences: Preferences,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SaveData {
+    pub version: u32,
+    /// How many levels, counting from the first, the player can pick from.
+    pub unlocked_levels: usize,
+    /// Records keyed by LDtk level identifier.
+    pub levels: BTreeMap<String, LevelRecord>,
+    pub settings: Settings,
+}
+
+impl Default for SaveData {
+    fn default() -> Self {
+        Self {
+            version: SAVE_VERSION,
+            unlocked_levels: 1,
+            levels: BTreeMap::new(),
+            settings: Settings::default(),
+        }
+    }
+}
+
+impl SaveData {
+    /// Reads a save of any version up to [`SAVE_VERSION`].
+    pub fn from_json(json: &str) -> Result<Self, SaveError> {
+        let mut value: Value = serde_json::from_str(json)?;
+        let version = value
+            .get("version")
+            .and_then(Value::as_u64)
+            .ok_or(SaveError::MissingVersion)? as u32;
+        if version > SAVE_VERSION {
+            return Err(SaveError::TooNew(version));
+        }
+
+        for migration in MIGRATIONS.iter().skip(version.saturating_sub(1) as usize) {
+            migration(&mut value);
+        }
+        value["version"] = SAVE_VERSION.into();
+
+        Ok(serde_json::from_value(value)?)
+    }
+
+    pub fn to_json(&self) -> Result<String, SaveError> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    /// Makes levels up to and including `level_index` playable.
+    pub fn unlock(&mut self, level_index: usize) {
+        self.unlocked_levels = self.unlocked_levels.max(level_index + 1);
+    }
+
+    /// Loads the save from where this platform keeps it. Having no save yet
+    /// isn't an error, it gives a fresh one.
+    pub fn load() -> Result<Self, SaveError> {
+        match storage::read(SAVE_FILE)? {
+            Some(json) => Self::from_json(&json),
+            None => Ok(Self::default()),
+        }
+    }
+
+    pub fn store(&self) -> Result<(), SaveError> {
+        Ok(storage::write(SAVE_FILE, &self.to_json()?)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn saves_survive_json() {
+        let mut save = SaveData::default();
+        save.unlock(1);
+        save.settings.movement_mode = MovementMode::Grid;
+        save.levels
+            .entry("Level_0".to_string())
+            .or_default()
+            .update(20, 30);
+
+        assert_eq!(SaveData::from_json(&save.to_json().unwrap()).unwrap(), save);
+    }
+
+    #[test]
+    fn records_keep_the_best_of_each() {
+        let mut record = LevelRecord::default();
+        record.update(20, 30);
+        record.update(18, 45);
+
+        assert_eq!(
+            record,
+            LevelRecord {
+                best_moves: Some(18),
+                best_minutes: Some(30),
+            }
+        );
+    }
+
+    #[test]
+    fn missing_settings_read_as_defaults() {
+        let save = SaveData::from_json(
+            r#"{"version": 1, "unlocked_levels": 1, "levels": {}, "settings": {}}"#,
+        )
+        .unwrap();
+
+        assert_eq!(save.settings, Settings::default());
+    }
+
+    #[test]
+    fn rejects_saves_it_cannot_read() {
+        assert!(matches!(
+            SaveData::from_json(&format!(r#"{{"version": {}}}"#, SAVE_VERSION + 1)),
+            Err(SaveError::TooNew(version)) if version == SAVE_VERSION + 1
+        ));
+        assert!(matches!(
+            SaveData::from_json("{}"),
+            Err(SaveError::MissingVersion)
+        ));
+    }
+}
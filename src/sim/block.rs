@@ -0,0 +1,283 @@
+use bevy_ecs_ldtk::{ldtk::EntityInstance, GridCoords};
+
+use super::{fields, identifiers, Direction, Level, LevelState, Seasion, TimeState};
+
+/// Optional crate field giving how many days the crate lasts before it rots
+/// away. Crates without it never rot.
+const ROTS_AFTER_FIELD: &str = "Rots_after";
+
+/// What a pushable block is made of.
+#[derive(Debug, PartialEq, Eq, Copy, Clone, Hash)]
+pub enum BlockKind {
+    /// A crate that lasts forever.
+    Crate,
+    /// A crate that rots away once the level has run for `rots_after` days,
+    /// and comes back if the clock runs back before then.
+    Wooden { rots_after: i32 },
+    /// A block of ice that melts in Summer and freezes again in Winter.
+    Ice,
+}
+
+/// A block the player can push around by walking into it.
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+pub struct Block {
+    /// The LDtk iid of the entity, so the game can find its sprite.
+    pub iid: String,
+    pub start: GridCoords,
+    pub kind: BlockKind,
+}
+
+impl Block {
+    /// Reads a `Crate` or `Ice_block` entity.
+    pub fn from_ldtk(entity: &EntityInstance, grid_coords: GridCoords) -> Self {
+        let kind = if entity.identifier == identifiers::ICE_BLOCK {
+            BlockKind::Ice
+        } else {
+            match fields::maybe_int(entity, ROTS_AFTER_FIELD) {
+                Some(rots_after) => BlockKind::Wooden { rots_after },
+                None => BlockKind::Crate,
+            }
+        };
+
+        Self {
+            iid: entity.iid.clone(),
+            start: grid_coords,
+            kind,
+        }
+    }
+}
+
+/// Where a block has been pushed to, and whether it has melted.
+#[derive(Debug, PartialEq, Eq, Copy, Clone, Hash)]
+pub struct BlockState {
+    pub grid_coords: GridCoords,
+    /// Only ever set for ice, which stays melted through Autumn until Winter
+    /// comes round.
+    pub melted: bool,
+}
+
+impl Level {
+    /// Whether the block at `index` in [`Level::blocks`] is still there, or
+    /// has rotted or melted away.
+    pub fn block_present(
+        &self,
+        index: usize,
+        level_state: &LevelState,
+        time_state: &TimeState,
+    ) -> bool {
+        match self.blocks[index].kind {
+            BlockKind::Crate => true,
+            BlockKind::Wooden { rots_after } => time_state.days_since_start() < rots_after,
+            BlockKind::Ice => match time_state.current_seasion() {
+                Seasion::Summer => false,
+                Seasion::Winter => true,
+                _ => !level_state.blocks[index].melted,
+            },
+        }
+    }
+
+    /// The index of the block standing on `grid_coords`, if there is one.
+    pub fn block_at(
+        &self,
+        grid_coords: &GridCoords,
+        level_state: &LevelState,
+        time_state: &TimeState,
+    ) -> Option<usize> {
+        (0..self.blocks.len()).find(|&index| {
+            level_state.blocks[index].grid_coords == *grid_coords
+                && self.block_present(index, level_state, time_state)
+        })
+    }
+
+    /// Pushes the block on `grid_coords` one tile in `direction`, if there is
+    /// one. Gives false if the block can't move, leaving everything as it
+    /// was.
+    ///
+    /// A block can't be pushed onto a tile holding another block, even one
+    /// that has rotted or melted away, so two blocks never share a tile when
+    /// the clock brings them back.
+    pub(crate) fn push_block(
+        &self,
+        grid_coords: &GridCoords,
+        direction: Direction,
+        level_state: &mut LevelState,
+        time_state: &TimeState,
+        player: &GridCoords,
+    ) -> bool {
+        let Some(index) = self.block_at(grid_coords, level_state, time_state) else {
+            return true;
+        };
+
+        let beyond = *grid_coords + GridCoords::from(direction.offset());
+        if self.impassable(&beyond, time_state, level_state, player)
+            || level_state
+                .blocks
+                .iter()
+                .any(|block| block.grid_coords == beyond)
+        {
+            return false;
+        }
+        level_state.blocks[index].grid_coords = beyond;
+        true
+    }
+
+    /// Melts ice if the clock is in Summer or came through it last on its way
+    /// from `before`, and freezes it again the same way for Winter. Orbs can
+    /// jump the clock across a whole season, so looking at where it ends up
+    /// isn't enough.
+    pub(crate) fn weather_blocks(
+        &self,
+        level_state: &mut LevelState,
+        before: &TimeState,
+        after: &TimeState,
+    ) {
+        let melted = match after.current_seasion() {
+            Seasion::Summer => true,
+            Seasion::Winter => false,
+            _ => match after.seasion_passed_from(before) {
+                Some(Seasion::Summer) => true,
+                Some(Seasion::Winter) => false,
+                _ => return,
+            },
+        };
+        for (block, state) in self.blocks.iter().zip(&mut level_state.blocks) {
+            if block.kind == BlockKind::Ice {
+                state.melted = melted;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::utils::HashSet;
+    use bevy_ecs_ldtk::ldtk::FieldValue;
+
+    use super::*;
+    use crate::sim::{
+        level::tests::{entity, with_field},
+        Step, Walls, World,
+    };
+
+    fn block(kind: BlockKind, x: i32) -> Block {
+        Block {
+            iid: format!("block-{x}"),
+            start: GridCoords::new(x, 0),
+            kind,
+        }
+    }
+
+    fn corridor(blocks: Vec<Block>) -> World {
+        World::new(Level {
+            blocks,
+            ..Level::empty(4, 1)
+        })
+    }
+
+    #[test]
+    fn reads_the_kind_of_block() {
+        let wooden = with_field(
+            entity(identifiers::CRATE, 0, 0),
+            ROTS_AFTER_FIELD,
+            FieldValue::Int(Some(2)),
+        );
+        let kind = |entity| Block::from_ldtk(&entity, GridCoords::default()).kind;
+
+        assert_eq!(kind(entity(identifiers::CRATE, 0, 0)), BlockKind::Crate);
+        assert_eq!(kind(wooden), BlockKind::Wooden { rots_after: 2 });
+        assert_eq!(kind(entity(identifiers::ICE_BLOCK, 0, 0)), BlockKind::Ice);
+    }
+
+    #[test]
+    fn walking_into_a_block_pushes_it() {
+        let mut world = corridor(vec![block(BlockKind::Crate, 1)]);
+
+        assert!(world.step(Step::Tile(Direction::Right)).moved);
+        assert_eq!(world.player.grid_coords, GridCoords::new(1, 0));
+        assert_eq!(
+            world.level_state.blocks[0].grid_coords,
+            GridCoords::new(2, 0)
+        );
+    }
+
+    #[test]
+    fn blocks_stop_at_walls_and_other_blocks() {
+        let mut world = corridor(vec![block(BlockKind::Crate, 1), block(BlockKind::Crate, 2)]);
+        assert!(!world.step(Step::Tile(Direction::Right)).moved);
+
+        let mut world = corridor(vec![block(BlockKind::Crate, 1)]);
+        world.level.walls = Walls::new(HashSet::from_iter([GridCoords::new(2, 0)]), 4, 1);
+        assert!(!world.step(Step::Tile(Direction::Right)).moved);
+        assert_eq!(world.level_state, world.level.initial_state());
+    }
+
+    #[test]
+    fn blocks_cannot_share_a_tile_with_a_rotted_one() {
+        let mut world = corridor(vec![
+            block(BlockKind::Crate, 1),
+            block(BlockKind::Wooden { rots_after: 1 }, 2),
+        ]);
+        world.time_state.time = 24 * 60;
+        assert!(!world
+            .level
+            .block_present(1, &world.level_state, &world.time_state));
+
+        assert!(!world.step(Step::Tile(Direction::Right)).moved);
+    }
+
+    #[test]
+    fn wooden_crates_rot_and_come_back() {
+        let mut world = corridor(vec![block(BlockKind::Wooden { rots_after: 1 }, 1)]);
+        let present = |world: &World| {
+            world
+                .level
+                .block_present(0, &world.level_state, &world.time_state)
+        };
+
+        world.time_state.time = 24 * 60 - 1;
+        assert!(present(&world));
+        world.time_state.time += 1;
+        assert!(!present(&world));
+        assert!(world.step(Step::Tile(Direction::Right)).moved);
+        assert_eq!(
+            world.level_state.blocks[0].grid_coords,
+            GridCoords::new(1, 0)
+        );
+
+        world.time_state.time = 0;
+        assert!(present(&world));
+    }
+
+    #[test]
+    fn ice_melts_in_summer_and_stays_melted_until_winter() {
+        let mut world = corridor(vec![block(BlockKind::Ice, 2)]);
+        let present = |world: &World| {
+            world
+                .level
+                .block_present(0, &world.level_state, &world.time_state)
+        };
+
+        // Jumping straight over Summer still melts the ice on the way.
+        let before = world.time_state;
+        world.time_state.jump_to(None, Some(Seasion::Autum));
+        world
+            .level
+            .weather_blocks(&mut world.level_state, &before, &world.time_state);
+        assert!(!present(&world));
+
+        let before = world.time_state;
+        world.time_state.jump_to(None, Some(Seasion::Winter));
+        world
+            .level
+            .weather_blocks(&mut world.level_state, &before, &world.time_state);
+        assert!(present(&world));
+
+        // Back into Autumn from Winter, the ice stays frozen.
+        let before = world.time_state;
+        world.time_state.jump_to(None, Some(Seasion::Autum));
+        world
+            .level
+            .weather_blocks(&mut world.level_state, &before, &world.time_state);
+        assert!(present(&world));
+    }
+}
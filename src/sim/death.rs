@@ -0,0 +1,80 @@
+use bevy_ecs_ldtk::GridCoords;
+use thiserror::Error;
+
+use super::{Level, TimeState};
+
+/// Why the player failed a level. The message is shown on the death screen.
+#[derive(Debug, Error, PartialEq, Eq, Copy, Clone, Hash)]
+pub enum DeathCause {
+    #[error("you walked into a hazard")]
+    Hazard,
+    #[error("the clock ran past the level's deadline of {0} minutes")]
+    Deadline(i32),
+    #[error("you ran into something hostile")]
+    Hostile,
+    #[error("you were caught by a patrol")]
+    Patrol,
+}
+
+impl Level {
+    /// What kills a player standing on `grid_coords` at this time, if anything.
+    pub fn death_cause(
+        &self,
+        grid_coords: &GridCoords,
+        time_state: &TimeState,
+    ) -> Option<DeathCause> {
+        if self.hazards.contains(grid_coords) {
+            Some(DeathCause::Hazard)
+        } else if self.hostiles.contains(grid_coords) {
+            Some(DeathCause::Hostile)
+        } else if self
+            .patrols
+            .iter()
+            .any(|patrol| patrol.position(time_state) == Some(*grid_coords))
+        {
+            Some(DeathCause::Patrol)
+        } else {
+            self.deadline
+                .filter(|deadline| time_state.time > *deadline)
+                .map(DeathCause::Deadline)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sim::{Direction, Step, World};
+
+    #[test]
+    fn walking_into_danger_kills() {
+        let mut level = Level::empty(4, 1);
+        level.hazards.insert(GridCoords::new(1, 0));
+        level.hostiles.push(GridCoords::new(2, 0));
+        let time_state = TimeState::default();
+
+        assert_eq!(level.death_cause(&GridCoords::new(0, 0), &time_state), None);
+        assert_eq!(
+            level.death_cause(&GridCoords::new(1, 0), &time_state),
+            Some(DeathCause::Hazard)
+        );
+        assert_eq!(
+            level.death_cause(&GridCoords::new(2, 0), &time_state),
+            Some(DeathCause::Hostile)
+        );
+    }
+
+    #[test]
+    fn running_past_the_deadline_kills() {
+        let mut world = World::new(Level {
+            deadline: Some(1),
+            ..Level::empty(3, 1)
+        });
+
+        assert_eq!(world.step(Step::Tile(Direction::Right)).died, None);
+        assert_eq!(
+            world.step(Step::Tile(Direction::Right)).died,
+            Some(DeathCause::Deadline(1))
+        );
+    }
+}
@@ -0,0 +1,242 @@
+//! Echoes of the player's past. In levels that have them, the player leaves a
+//! footprint wherever they stand as the clock moves on, and an echo stands on
+//! every footprint left at the time the clock now shows. Running the clock
+//! backwards walks the echoes back along the path.
+
+use bevy_ecs_ldtk::{ldtk::EntityInstance, GridCoords};
+
+use super::{fields, Gate, Level, LevelState, TimeState};
+
+/// Optional field on pressure plates and gates pairing them up. Plates and
+/// gates without it are on channel 0.
+const CHANNEL_FIELD: &str = "Channel";
+
+/// A tile that holds the gates on its channel open while the player or an
+/// echo stands on it.
+#[derive(Debug, PartialEq, Eq, Copy, Clone, Hash)]
+pub struct PressurePlate {
+    pub grid_coords: GridCoords,
+    pub channel: i32,
+}
+
+impl PressurePlate {
+    pub fn from_ldtk(entity: &EntityInstance, grid_coords: GridCoords) -> Self {
+        Self {
+            grid_coords,
+            channel: plate_channel(entity).unwrap_or_default(),
+        }
+    }
+}
+
+/// The channel a plate or gate is on, if it names one.
+pub fn plate_channel(entity: &EntityInstance) -> Option<i32> {
+    fields::maybe_int(entity, CHANNEL_FIELD)
+}
+
+impl Level {
+    /// Records that the player stood on `grid_coords` as the clock left
+    /// `time`. Does nothing in levels without echoes.
+    pub(crate) fn leave_footprint(
+        &self,
+        level_state: &mut LevelState,
+        time: i32,
+        grid_coords: GridCoords,
+    ) {
+        if !self.echoes {
+            return;
+        }
+        let tiles = level_state.footprints.entry(time).or_default();
+        if !tiles.contains(&grid_coords) {
+            tiles.push(grid_coords);
+        }
+    }
+
+    /// Where the echoes stand at this time. Echoes on the player's own tile
+    /// are left out, as the player is standing in their own footsteps.
+    pub fn echoes<'a>(
+        &'a self,
+        level_state: &'a LevelState,
+        time_state: &'a TimeState,
+        player: &'a GridCoords,
+    ) -> impl Iterator<Item = GridCoords> + 'a {
+        level_state
+            .footprints
+            .get(&time_state.time)
+            .into_iter()
+            .flatten()
+            .copied()
+            .filter(move |grid_coords| grid_coords != player)
+    }
+
+    pub fn echo_at(
+        &self,
+        grid_coords: &GridCoords,
+        level_state: &LevelState,
+        time_state: &TimeState,
+        player: &GridCoords,
+    ) -> bool {
+        self.echoes(level_state, time_state, player)
+            .any(|echo| echo == *grid_coords)
+    }
+
+    /// Whether the player, an echo or a block is standing on `plate`.
+    pub fn plate_held(
+        &self,
+        plate: &PressurePlate,
+        level_state: &LevelState,
+        time_state: &TimeState,
+        player: &GridCoords,
+    ) -> bool {
+        plate.grid_coords == *player
+            || self.echo_at(&plate.grid_coords, level_state, time_state, player)
+            || self
+                .block_at(&plate.grid_coords, level_state, time_state)
+                .is_some()
+    }
+
+    /// Whether `gate` blocks the way. Gates on a channel are held open by
+    /// their plates and closed otherwise, the rest follow their schedule.
+    pub fn gate_closed(
+        &self,
+        gate: &Gate,
+        level_state: &LevelState,
+        time_state: &TimeState,
+        player: &GridCoords,
+    ) -> bool {
+        match gate.channel {
+            Some(channel) => !self
+                .pressure_plates
+                .iter()
+                .filter(|plate| plate.channel == channel)
+                .any(|plate| self.plate_held(plate, level_state, time_state, player)),
+            None => gate.schedule.is_closed(time_state),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy_ecs_ldtk::ldtk::FieldValue;
+
+    use super::*;
+    use crate::sim::{
+        identifiers,
+        level::tests::{entity, with_field},
+        Block, BlockKind, Direction, GateSchedule, Step, World,
+    };
+
+    /// A corridor with a plate on the first tile after the start and, up
+    /// from the start, a gate on the plate's channel.
+    fn plate_and_gate(echoes: bool) -> World {
+        World::new(Level {
+            pressure_plates: vec![PressurePlate {
+                grid_coords: GridCoords::new(1, 0),
+                channel: 1,
+            }],
+            gates: vec![Gate {
+                grid_coords: GridCoords::new(0, 1),
+                schedule: GateSchedule::default(),
+                channel: Some(1),
+            }],
+            echoes,
+            ..Level::empty(3, 2)
+        })
+    }
+
+    #[test]
+    fn plates_default_to_channel_zero() {
+        let plate = |entity| PressurePlate::from_ldtk(&entity, GridCoords::default()).channel;
+
+        assert_eq!(plate(entity(identifiers::PRESSURE_PLATE, 0, 0)), 0);
+        assert_eq!(
+            plate(with_field(
+                entity(identifiers::PRESSURE_PLATE, 0, 0),
+                CHANNEL_FIELD,
+                FieldValue::Int(Some(2))
+            )),
+            2
+        );
+    }
+
+    #[test]
+    fn footprints_are_only_left_in_levels_with_echoes() {
+        for echoes in [false, true] {
+            let mut world = plate_and_gate(echoes);
+            world.step(Step::Tile(Direction::Right));
+            // Off the time axis, so no footprint.
+            world.step(Step::Tile(Direction::Up));
+
+            assert_eq!(world.level_state.footprints.len(), usize::from(echoes));
+        }
+    }
+
+    #[test]
+    fn echoes_stand_where_the_player_stood_at_that_time() {
+        let mut world = plate_and_gate(true);
+        let echoes = |world: &World| {
+            world
+                .level
+                .echoes(
+                    &world.level_state,
+                    &world.time_state,
+                    &world.player.grid_coords,
+                )
+                .collect::<Vec<_>>()
+        };
+
+        for direction in [Direction::Right, Direction::Right, Direction::Left] {
+            world.step(Step::Tile(direction));
+        }
+        // Back on the plate at the time they left it, in their own footsteps.
+        assert_eq!(echoes(&world), vec![]);
+
+        world.step(Step::Tile(Direction::Up));
+        assert_eq!(echoes(&world), vec![GridCoords::new(1, 0)]);
+        // An echo is in the way like anything else.
+        assert!(!world.step(Step::Tile(Direction::Down)).moved);
+    }
+
+    #[test]
+    fn an_echo_on_the_plate_holds_the_gate_open() {
+        for echoes in [false, true] {
+            let mut world = plate_and_gate(echoes);
+            for direction in [
+                Direction::Right,
+                Direction::Right,
+                Direction::Left,
+                Direction::Up,
+                Direction::Left,
+            ] {
+                world.step(Step::Tile(direction));
+            }
+
+            assert_eq!(world.player.grid_coords == GridCoords::new(0, 1), echoes);
+        }
+    }
+
+    #[test]
+    fn the_player_or_a_block_holds_the_plate_down() {
+        let gate_closed = |world: &World| {
+            world.level.gate_closed(
+                &world.level.gates[0],
+                &world.level_state,
+                &world.time_state,
+                &world.player.grid_coords,
+            )
+        };
+
+        let mut world = plate_and_gate(false);
+        assert!(gate_closed(&world));
+        world.step(Step::Tile(Direction::Right));
+        assert!(!gate_closed(&world));
+
+        let mut world = plate_and_gate(false);
+        world.level.blocks.push(Block {
+            iid: "crate".to_string(),
+            start: GridCoords::new(1, 0),
+            kind: BlockKind::Crate,
+        });
+        world.level_state = world.level.initial_state();
+        assert!(!gate_closed(&world));
+    }
+}
@@ -0,0 +1,84 @@
+//! Helpers for the optional LDtk fields entities use to configure the rules.
+//! A field that is missing from the entity definition reads as unset.
+
+use bevy_ecs_ldtk::ldtk::{ldtk_fields::LdtkFields, EntityInstance};
+
+use super::{HourRange, LevelError, Seasion, TimeAxis};
+
+pub fn maybe_int(entity: &EntityInstance, identifier: &str) -> Option<i32> {
+    entity
+        .get_maybe_int_field(identifier)
+        .ok()
+        .copied()
+        .flatten()
+}
+
+pub fn maybe_seasion(
+    entity: &EntityInstance,
+    identifier: &str,
+) -> Result<Option<Seasion>, LevelError> {
+    match entity.get_maybe_enum_field(identifier) {
+        Ok(Some(value)) => Seasion::from_identifier(value)
+            .map(Some)
+            .ok_or_else(|| LevelError::UnknownSeasion(entity.identifier.clone(), value.clone())),
+        _ => Ok(None),
+    }
+}
+
+pub fn maybe_time_axis(
+    entity: &EntityInstance,
+    identifier: &str,
+) -> Result<Option<TimeAxis>, LevelError> {
+    match entity.get_maybe_enum_field(identifier) {
+        Ok(Some(value)) => TimeAxis::from_identifier(value)
+            .map(Some)
+            .ok_or_else(|| LevelError::UnknownTimeAxis(entity.identifier.clone(), value.clone())),
+        _ => Ok(None),
+    }
+}
+
+/// Reads an hour range from a pair of hour fields. Leaving out one end runs
+/// the range from midnight or up to midnight.
+pub fn maybe_hours(
+    entity: &EntityInstance,
+    from_identifier: &str,
+    until_identifier: &str,
+) -> Option<HourRange> {
+    match (
+        maybe_int(entity, from_identifier),
+        maybe_int(entity, until_identifier),
+    ) {
+        (None, None) => None,
+        (from, until) => Some(HourRange {
+            from: from.unwrap_or(0),
+            until: until.unwrap_or(24),
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy_ecs_ldtk::ldtk::FieldValue;
+
+    use super::*;
+    use crate::sim::level::tests::{entity, with_field};
+
+    #[test]
+    fn missing_and_null_fields_read_as_unset() {
+        let entity = with_field(entity("Thing", 0, 0), "Empty", FieldValue::Int(None));
+
+        assert_eq!(maybe_int(&entity, "Empty"), None);
+        assert_eq!(maybe_int(&entity, "Missing"), None);
+        assert_eq!(maybe_hours(&entity, "Empty", "Missing"), None);
+    }
+
+    #[test]
+    fn hour_ranges_default_to_midnight() {
+        let entity = with_field(entity("Thing", 0, 0), "Until", FieldValue::Int(Some(6)));
+
+        assert_eq!(
+            maybe_hours(&entity, "From", "Until"),
+            Some(HourRange { from: 0, until: 6 })
+        );
+    }
+}
@@ -0,0 +1,134 @@
+use bevy_ecs_ldtk::{ldtk::EntityInstance, GridCoords};
+
+use super::{echo, fields, HourRange, LevelError, Seasion, TimeState};
+
+const CLOSED_FROM_FIELD: &str = "Closed_from";
+const CLOSED_UNTIL_FIELD: &str = "Closed_until";
+const CLOSED_IN_FIELD: &str = "Closed_in";
+
+/// When a time gate blocks the way. A gate with neither an hour range nor a
+/// season is always closed.
+#[derive(Debug, Default, PartialEq, Eq, Copy, Clone, Hash)]
+pub struct GateSchedule {
+    pub hours: Option<HourRange>,
+    pub seasion: Option<Seasion>,
+}
+
+impl GateSchedule {
+    /// Reads the schedule from a `Time_gate` entity's optional `Closed_from`
+    /// and `Closed_until` hour fields and its `Closed_in` season field.
+    pub fn from_ldtk(entity: &EntityInstance) -> Result<Self, LevelError> {
+        Ok(Self {
+            hours: fields::maybe_hours(entity, CLOSED_FROM_FIELD, CLOSED_UNTIL_FIELD),
+            seasion: fields::maybe_seasion(entity, CLOSED_IN_FIELD)?,
+        })
+    }
+
+    pub fn is_closed(&self, time_state: &TimeState) -> bool {
+        let in_hours = self
+            .hours
+            .is_none_or(|hours| hours.contains(time_state.current_hour()));
+        let in_seasion = self
+            .seasion
+            .is_none_or(|seasion| seasion == time_state.current_seasion());
+
+        in_hours && in_seasion
+    }
+}
+
+/// A wall that only blocks the way at certain times, or that pressure plates
+/// hold open.
+#[derive(Debug, PartialEq, Eq, Copy, Clone, Hash)]
+pub struct Gate {
+    pub grid_coords: GridCoords,
+    pub schedule: GateSchedule,
+    /// The pressure plate channel that opens the gate, if any. The schedule
+    /// is ignored for gates on a channel.
+    pub channel: Option<i32>,
+}
+
+impl Gate {
+    pub fn from_ldtk(entity: &EntityInstance, grid_coords: GridCoords) -> Result<Self, LevelError> {
+        Ok(Self {
+            grid_coords,
+            schedule: GateSchedule::from_ldtk(entity)?,
+            channel: echo::plate_channel(entity),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy_ecs_ldtk::ldtk::FieldValue;
+
+    use super::*;
+    use crate::{
+        consts::DAYS_PER_SEASION,
+        sim::{
+            identifiers,
+            level::tests::{entity, with_field},
+            Direction, Level, Step, World,
+        },
+    };
+
+    fn at_hour(hour: i32) -> TimeState {
+        TimeState {
+            time: hour * 60,
+            ..TimeState::default()
+        }
+    }
+
+    #[test]
+    fn closes_during_its_hours_and_season() {
+        let schedule = GateSchedule {
+            hours: Some(HourRange { from: 6, until: 18 }),
+            seasion: Some(Seasion::Spring),
+        };
+
+        assert!(schedule.is_closed(&at_hour(6)));
+        assert!(!schedule.is_closed(&at_hour(18)));
+        assert!(!schedule.is_closed(&at_hour(DAYS_PER_SEASION * 24 + 12)));
+    }
+
+    #[test]
+    fn closes_all_the_time_without_a_schedule() {
+        assert!(GateSchedule::default().is_closed(&at_hour(3)));
+    }
+
+    #[test]
+    fn reads_one_hour_as_running_to_or_from_midnight() {
+        let gate = with_field(
+            entity(identifiers::TIME_GATE, 0, 0),
+            CLOSED_FROM_FIELD,
+            FieldValue::Int(Some(20)),
+        );
+
+        assert_eq!(
+            GateSchedule::from_ldtk(&gate).unwrap().hours,
+            Some(HourRange {
+                from: 20,
+                until: 24
+            })
+        );
+    }
+
+    #[test]
+    fn closed_gates_block_the_way() {
+        let mut world = World::new(Level {
+            gates: vec![Gate {
+                grid_coords: GridCoords::new(1, 0),
+                schedule: GateSchedule {
+                    hours: Some(HourRange { from: 0, until: 1 }),
+                    seasion: None,
+                },
+                channel: None,
+            }],
+            ..Level::empty(2, 1)
+        });
+
+        assert!(!world.step(Step::Tile(Direction::Right)).moved);
+
+        world.time_state = at_hour(1);
+        assert!(world.step(Step::Tile(Direction::Right)).moved);
+    }
+}
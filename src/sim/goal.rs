@@ -0,0 +1,111 @@
+use bevy_ecs_ldtk::{ldtk::EntityInstance, GridCoords};
+use thiserror::Error;
+
+use super::{fields, HourRange, LevelError, Seasion, TimeState};
+
+const ARRIVE_FROM_FIELD: &str = "Arrive_from";
+const ARRIVE_UNTIL_FIELD: &str = "Arrive_until";
+const ARRIVE_IN_FIELD: &str = "Arrive_in";
+const MAX_TIME_FIELD: &str = "Max_time";
+
+/// One condition the clock has to meet for a goal to accept the player. The
+/// message reads as an instruction to the player.
+#[derive(Debug, Error, PartialEq, Eq, Copy, Clone, Hash)]
+pub enum Requirement {
+    #[error("arrive between {0}")]
+    Hours(HourRange),
+    #[error("arrive in {0}")]
+    Seasion(Seasion),
+    #[error("arrive before the clock passes {0} minutes")]
+    MaxTime(i32),
+}
+
+impl Requirement {
+    pub fn is_met(&self, time_state: &TimeState) -> bool {
+        match self {
+            Requirement::Hours(hours) => hours.contains(time_state.current_hour()),
+            Requirement::Seasion(seasion) => *seasion == time_state.current_seasion(),
+            Requirement::MaxTime(max_time) => time_state.time <= *max_time,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Goal {
+    pub grid_coords: GridCoords,
+    pub requirements: Vec<Requirement>,
+}
+
+impl Goal {
+    /// Reads a `Goal` entity's optional `Arrive_from` and `Arrive_until` hour
+    /// fields, `Arrive_in` season field and `Max_time` field.
+    pub fn from_ldtk(entity: &EntityInstance, grid_coords: GridCoords) -> Result<Self, LevelError> {
+        let mut requirements = Vec::new();
+        if let Some(hours) = fields::maybe_hours(entity, ARRIVE_FROM_FIELD, ARRIVE_UNTIL_FIELD) {
+            requirements.push(Requirement::Hours(hours));
+        }
+        if let Some(seasion) = fields::maybe_seasion(entity, ARRIVE_IN_FIELD)? {
+            requirements.push(Requirement::Seasion(seasion));
+        }
+        if let Some(max_time) = fields::maybe_int(entity, MAX_TIME_FIELD) {
+            requirements.push(Requirement::MaxTime(max_time));
+        }
+
+        Ok(Self {
+            grid_coords,
+            requirements,
+        })
+    }
+
+    /// The requirements the clock doesn't meet yet.
+    pub fn unmet(&self, time_state: &TimeState) -> impl Iterator<Item = &Requirement> + '_ {
+        let time_state = *time_state;
+        self.requirements
+            .iter()
+            .filter(move |requirement| !requirement.is_met(&time_state))
+    }
+
+    pub fn accepts(&self, time_state: &TimeState) -> bool {
+        self.unmet(time_state).next().is_none()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accepts_once_every_requirement_is_met() {
+        let goal = Goal {
+            grid_coords: GridCoords::default(),
+            requirements: vec![
+                Requirement::Hours(HourRange { from: 1, until: 2 }),
+                Requirement::MaxTime(90),
+            ],
+        };
+        let at = |time| TimeState {
+            time,
+            ..TimeState::default()
+        };
+
+        assert_eq!(
+            goal.unmet(&at(30)).collect::<Vec<_>>(),
+            vec![&Requirement::Hours(HourRange { from: 1, until: 2 })]
+        );
+        assert!(goal.accepts(&at(60)));
+        assert_eq!(
+            goal.unmet(&at(100)).collect::<Vec<_>>(),
+            vec![&Requirement::MaxTime(90)]
+        );
+    }
+
+    #[test]
+    fn goals_without_requirements_always_accept() {
+        let goal = Goal {
+            grid_coords: GridCoords::default(),
+            requirements: Vec::new(),
+        };
+
+        assert!(goal.accepts(&TimeState::default()));
+    }
+}
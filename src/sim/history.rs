@@ -0,0 +1,163 @@
+use std::collections::VecDeque;
+
+use super::{LevelState, Player, TimeState};
+use crate::consts;
+
+/// Everything needed to put a level back the way it was before a move.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Snapshot {
+    pub player: Player,
+    pub time_state: TimeState,
+    pub level_state: LevelState,
+}
+
+/// Undo and redo stacks of [`Snapshot`]s. Only the most recent `capacity`
+/// moves can be undone.
+#[derive(Debug, Clone)]
+pub struct History {
+    undo: VecDeque<Snapshot>,
+    redo: Vec<Snapshot>,
+    capacity: usize,
+}
+
+impl Default for History {
+    fn default() -> Self {
+        Self::new(consts::UNDO_HISTORY_SIZE)
+    }
+}
+
+impl History {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            undo: VecDeque::with_capacity(capacity),
+            redo: Vec::new(),
+            capacity,
+        }
+    }
+
+    /// Records the state from before a move. A new move throws away anything
+    /// that could have been redone.
+    pub fn record(&mut self, snapshot: Snapshot) {
+        self.redo.clear();
+        self.push_undo(snapshot);
+    }
+
+    /// Steps back one move, returning the state to restore. `current` is kept
+    /// so the move can be redone.
+    pub fn undo(&mut self, current: Snapshot) -> Option<Snapshot> {
+        let snapshot = self.undo.pop_back()?;
+        self.redo.push(current);
+        Some(snapshot)
+    }
+
+    /// Steps forward again after an [`History::undo`].
+    pub fn redo(&mut self, current: Snapshot) -> Option<Snapshot> {
+        let snapshot = self.redo.pop()?;
+        self.push_undo(current);
+        Some(snapshot)
+    }
+
+    pub fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+    }
+
+    fn push_undo(&mut self, snapshot: Snapshot) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.undo.len() == self.capacity {
+            self.undo.pop_front();
+        }
+        self.undo.push_back(snapshot);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy_ecs_ldtk::GridCoords;
+
+    use super::*;
+    use crate::sim::{Direction, Level, Step, World};
+
+    fn world() -> World {
+        World::new(Level::empty(5, 1))
+    }
+
+    /// Takes a step to the right, recording it the way the game does.
+    fn step(world: &mut World, history: &mut History) {
+        let snapshot = world.snapshot();
+        world.step(Step::Tile(Direction::Right));
+        history.record(snapshot);
+    }
+
+    fn undo(world: &mut World, history: &mut History) -> bool {
+        let snapshot = history.undo(world.snapshot());
+        snapshot.map(|snapshot| world.restore(snapshot)).is_some()
+    }
+
+    fn redo(world: &mut World, history: &mut History) -> bool {
+        let snapshot = history.redo(world.snapshot());
+        snapshot.map(|snapshot| world.restore(snapshot)).is_some()
+    }
+
+    #[test]
+    fn undo_and_redo_walk_through_the_moves() {
+        let mut world = world();
+        let mut history = History::default();
+        step(&mut world, &mut history);
+        step(&mut world, &mut history);
+
+        assert!(undo(&mut world, &mut history));
+        assert_eq!(world.player.grid_coords, GridCoords::new(1, 0));
+        assert_eq!(world.time_state.time, 1);
+
+        assert!(undo(&mut world, &mut history));
+        assert!(!undo(&mut world, &mut history));
+        assert_eq!(world.snapshot(), self::world().snapshot());
+
+        assert!(redo(&mut world, &mut history));
+        assert!(redo(&mut world, &mut history));
+        assert!(!redo(&mut world, &mut history));
+        assert_eq!(world.player.grid_coords, GridCoords::new(2, 0));
+        assert_eq!(world.time_state.time, 2);
+    }
+
+    #[test]
+    fn a_new_move_forgets_the_redos() {
+        let mut world = world();
+        let mut history = History::default();
+        step(&mut world, &mut history);
+        undo(&mut world, &mut history);
+
+        step(&mut world, &mut history);
+        assert!(!redo(&mut world, &mut history));
+    }
+
+    #[test]
+    fn only_keeps_the_latest_moves() {
+        let mut world = world();
+        let mut history = History::new(2);
+        for _ in 0..3 {
+            step(&mut world, &mut history);
+        }
+
+        assert!(undo(&mut world, &mut history));
+        assert!(undo(&mut world, &mut history));
+        assert!(!undo(&mut world, &mut history));
+        assert_eq!(world.player.grid_coords, GridCoords::new(1, 0));
+    }
+
+    #[test]
+    fn clear_forgets_everything() {
+        let mut world = world();
+        let mut history = History::default();
+        step(&mut world, &mut history);
+        step(&mut world, &mut history);
+        undo(&mut world, &mut history);
+
+        history.clear();
+        assert!(!undo(&mut world, &mut history));
+        assert!(!redo(&mut world, &mut history));
+    }
+}
@@ -0,0 +1,34 @@
+//! The LDtk entity identifiers the game spawns bundles for.
+
+pub const PLAYER: &str = "Player";
+pub const GOAL: &str = "Goal";
+pub const AXIS_SWITCH: &str = "Axis_switch";
+pub const DIRECTION_SWITCH: &str = "Direction_switch";
+pub const SPEED_UP: &str = "Speed_up";
+pub const SLOW_DOWN: &str = "Slow_down";
+pub const FREEZE: &str = "Freeze";
+pub const UNFREEZE: &str = "Unfreeze";
+pub const RESET: &str = "Reset";
+pub const JUMP: &str = "Jump";
+pub const TIME_GATE: &str = "Time_gate";
+pub const HOSTILE: &str = "Hostile";
+pub const PRESSURE_PLATE: &str = "Pressure_plate";
+pub const PATROL: &str = "Patrol";
+pub const CRATE: &str = "Crate";
+pub const ICE_BLOCK: &str = "Ice_block";
+pub const PLANT: &str = "Plant";
+
+/// Every entity identifier registered with `register_ldtk_entity`, apart
+/// from orbs, which come from the `OrbRegistry`. Anything else placed in a
+/// level is silently dropped when the level spawns.
+pub const REGISTERED: &[&str] = &[
+    PLAYER,
+    GOAL,
+    TIME_GATE,
+    HOSTILE,
+    PRESSURE_PLATE,
+    PATROL,
+    CRATE,
+    ICE_BLOCK,
+    PLANT,
+];
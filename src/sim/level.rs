@@ -0,0 +1,510 @@
+use bevy::{prelude::*, utils::HashSet};
+use bevy_ecs_ldtk::{
+    ldtk::{self, ldtk_fields::LdtkFields, Type},
+    utils::ldtk_grid_coords_to_grid_coords,
+    GridCoords,
+};
+use thiserror::Error;
+
+use super::{
+    identifiers, Block, Gate, Goal, LevelState, MovementMode, Orb, OrbRegistry, Patrol, Plant,
+    PressurePlate, Seasion, TimeOnEnter, TimeState,
+};
+use crate::consts::GRID_SIZE;
+
+/// The IntGrid value used for walls in the `Walls` layer.
+pub const WALL_INT_CELL: i32 = 1;
+
+/// The IntGrid value used for water in the `Walls` layer. Water freezes over
+/// and can be walked on in Winter only.
+pub const WATER_INT_CELL: i32 = 2;
+
+/// The IntGrid value used for hazards in the `Walls` layer. Walking onto one
+/// kills the player.
+pub const HAZARD_INT_CELL: i32 = 3;
+
+pub(crate) const WALLS_LAYER: &str = "Walls";
+
+/// Optional level field choosing how the player moves in that level.
+const MOVEMENT_FIELD: &str = "Movement";
+
+/// Optional level field choosing whether the clock carries over into the
+/// level. Levels without it start from the default time.
+const TIME_ON_ENTER_FIELD: &str = "Time_on_enter";
+
+/// Optional level fields with the name shown for the level and a line or two
+/// about it. Levels without a name show their identifier.
+const NAME_FIELD: &str = "Name";
+const DESCRIPTION_FIELD: &str = "Description";
+
+/// Optional level field giving the latest time, in minutes, the player can
+/// reach without dying. It is read against the absolute clock, not counted
+/// from when the level starts, so in a level that keeps the clock it includes
+/// the time spent in earlier levels.
+const DEADLINE_FIELD: &str = "Deadline";
+
+/// Optional level field turning on echoes of the player's past.
+const ECHOES_FIELD: &str = "Echoes";
+
+#[derive(Debug, Error)]
+pub enum LevelError {
+    #[error("level {0} has no layer instances, external levels are not supported")]
+    MissingLayers(String),
+    #[error("level {0} has no Player entity")]
+    MissingPlayer(String),
+    #[error("level {0} asks for unknown movement mode {1}")]
+    UnknownMovementMode(String, String),
+    #[error("level {0} asks for unknown time on enter {1}")]
+    UnknownTimeOnEnter(String, String),
+    #[error("{0} uses unknown season {1}")]
+    UnknownSeasion(String, String),
+    #[error("{0} uses unknown time axis {1}")]
+    UnknownTimeAxis(String, String),
+}
+
+/// Wall locations for a level, stored as a set to allow for a quick lookup.
+#[derive(Default, Debug, Clone)]
+pub struct Walls {
+    wall_locations: HashSet<GridCoords>,
+    level_width: i32,
+    level_height: i32,
+}
+
+impl Walls {
+    pub fn new(wall_locations: HashSet<GridCoords>, level_width: i32, level_height: i32) -> Self {
+        Self {
+            wall_locations,
+            level_width,
+            level_height,
+        }
+    }
+
+    pub fn in_wall(&self, grid_coords: &GridCoords) -> bool {
+        grid_coords.x < 0
+            || grid_coords.y < 0
+            || grid_coords.x >= self.level_width
+            || grid_coords.y >= self.level_height
+            || self.wall_locations.contains(grid_coords)
+    }
+
+    pub fn level_width(&self) -> i32 {
+        self.level_width
+    }
+
+    pub fn level_height(&self) -> i32 {
+        self.level_height
+    }
+}
+
+/// Everything the rules need to know about a level.
+#[derive(Default, Debug, Clone)]
+pub struct Level {
+    /// The LDtk identifier, such as `Level_0`.
+    pub identifier: String,
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub iid: String,
+    pub walls: Walls,
+    pub water: HashSet<GridCoords>,
+    pub hazards: HashSet<GridCoords>,
+    pub hostiles: Vec<GridCoords>,
+    pub patrols: Vec<Patrol>,
+    pub orbs: Vec<Orb>,
+    pub goals: Vec<Goal>,
+    pub gates: Vec<Gate>,
+    pub pressure_plates: Vec<PressurePlate>,
+    pub blocks: Vec<Block>,
+    pub plants: Vec<Plant>,
+    pub player_start: GridCoords,
+    /// Overrides the player's movement setting for this level.
+    pub movement_mode: Option<MovementMode>,
+    pub deadline: Option<i32>,
+    pub time_on_enter: TimeOnEnter,
+    /// Whether the player's past is replayed by echoes.
+    pub echoes: bool,
+}
+
+impl Level {
+    /// Builds the rules for a level straight from its LDtk description, without
+    /// going through the asset pipeline. Entities in `orb_registry` become
+    /// orbs.
+    pub fn from_ldtk(level: &ldtk::Level, orb_registry: &OrbRegistry) -> Result<Self, LevelError> {
+        let layers = level
+            .layer_instances
+            .as_ref()
+            .ok_or_else(|| LevelError::MissingLayers(level.identifier.clone()))?;
+
+        let level_width = level.px_wid / GRID_SIZE;
+        let level_height = level.px_hei / GRID_SIZE;
+
+        let mut wall_locations = HashSet::new();
+        let mut water = HashSet::new();
+        let mut hazards = HashSet::new();
+        let mut hostiles = Vec::new();
+        let mut patrols = Vec::new();
+        let mut orbs = Vec::new();
+        let mut goals = Vec::new();
+        let mut gates = Vec::new();
+        let mut pressure_plates = Vec::new();
+        let mut blocks = Vec::new();
+        let mut plants = Vec::new();
+        let mut player_start = None;
+
+        for layer in layers {
+            match layer.layer_instance_type {
+                Type::IntGrid if layer.identifier == WALLS_LAYER => {
+                    for (index, value) in layer.int_grid_csv.iter().enumerate() {
+                        let index = index as i32;
+                        let grid_coords = ldtk_grid_coords_to_grid_coords(
+                            IVec2::new(index % layer.c_wid, index / layer.c_wid),
+                            layer.c_hei,
+                        );
+                        match *value {
+                            WALL_INT_CELL => {
+                                wall_locations.insert(grid_coords);
+                            }
+                            WATER_INT_CELL => {
+                                water.insert(grid_coords);
+                            }
+                            HAZARD_INT_CELL => {
+                                hazards.insert(grid_coords);
+                            }
+                            _ => {}
+                        }
+                    }
+                }
+                Type::Entities => {
+                    for entity in &layer.entity_instances {
+                        let grid_coords = ldtk_grid_coords_to_grid_coords(entity.grid, layer.c_hei);
+                        match entity.identifier.as_str() {
+                            identifiers::PLAYER => player_start = Some(grid_coords),
+                            identifiers::GOAL => goals.push(Goal::from_ldtk(entity, grid_coords)?),
+                            identifiers::HOSTILE => hostiles.push(grid_coords),
+                            identifiers::PATROL => {
+                                patrols.push(Patrol::from_ldtk(entity, grid_coords, layer.c_hei))
+                            }
+                            identifiers::TIME_GATE => {
+                                gates.push(Gate::from_ldtk(entity, grid_coords)?)
+                            }
+                            identifiers::CRATE | identifiers::ICE_BLOCK => {
+                                blocks.push(Block::from_ldtk(entity, grid_coords))
+                            }
+                            identifiers::PLANT => {
+                                plants.push(Plant::from_ldtk(entity, grid_coords))
+                            }
+                            identifiers::PRESSURE_PLATE => {
+                                pressure_plates.push(PressurePlate::from_ldtk(entity, grid_coords))
+                            }
+                            _ => {
+                                if let Some(effect) = orb_registry.effect(entity)? {
+                                    orbs.push(Orb::from_ldtk(entity, grid_coords, effect)?);
+                                }
+                            }
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        let movement_mode = match level.get_maybe_enum_field(MOVEMENT_FIELD) {
+            Ok(Some(value)) => Some(MovementMode::from_identifier(value).ok_or_else(|| {
+                LevelError::UnknownMovementMode(level.identifier.clone(), value.clone())
+            })?),
+            _ => None,
+        };
+
+        let time_on_enter = match level.get_maybe_enum_field(TIME_ON_ENTER_FIELD) {
+            Ok(Some(value)) => TimeOnEnter::from_identifier(value).ok_or_else(|| {
+                LevelError::UnknownTimeOnEnter(level.identifier.clone(), value.clone())
+            })?,
+            _ => TimeOnEnter::default(),
+        };
+
+        let deadline = level
+            .get_maybe_int_field(DEADLINE_FIELD)
+            .ok()
+            .copied()
+            .flatten();
+
+        let echoes = level
+            .get_bool_field(ECHOES_FIELD)
+            .ok()
+            .copied()
+            .unwrap_or(false);
+
+        let string_field = |identifier| {
+            level
+                .get_maybe_string_field(identifier)
+                .ok()
+                .cloned()
+                .flatten()
+        };
+
+        Ok(Self {
+            identifier: level.identifier.clone(),
+            name: string_field(NAME_FIELD),
+            description: string_field(DESCRIPTION_FIELD),
+            iid: level.iid.clone(),
+            walls: Walls::new(wall_locations, level_width, level_height),
+            water,
+            hazards,
+            hostiles,
+            patrols,
+            orbs,
+            goals,
+            gates,
+            pressure_plates,
+            blocks,
+            plants,
+            player_start: player_start
+                .ok_or_else(|| LevelError::MissingPlayer(level.identifier.clone()))?,
+            movement_mode,
+            deadline,
+            time_on_enter,
+            echoes,
+        })
+    }
+
+    /// The name to show the player.
+    pub fn display_name(&self) -> String {
+        self.name
+            .clone()
+            .unwrap_or_else(|| self.identifier.replace('_', " "))
+    }
+
+    pub fn in_wall(&self, grid_coords: &GridCoords) -> bool {
+        self.walls.in_wall(grid_coords)
+    }
+
+    /// The clocks the level can start with for a player arriving with any of
+    /// `arrivals`, without repeats.
+    pub fn starts(&self, arrivals: &[TimeState]) -> Vec<TimeState> {
+        let mut starts = Vec::new();
+        for arriving in arrivals {
+            let start = self.time_on_enter.apply(*arriving);
+            if !starts.contains(&start) {
+                starts.push(start);
+            }
+        }
+        starts
+    }
+
+    pub fn gate_at(&self, grid_coords: &GridCoords) -> Option<&Gate> {
+        self.gates
+            .iter()
+            .find(|gate| gate.grid_coords == *grid_coords)
+    }
+
+    /// Whether nothing can move into `grid_coords` at this time, while the
+    /// player stands on `player`: a wall, a grown tree, a closed gate, water
+    /// that hasn't frozen, an echo or a block is in the way.
+    pub fn blocked(
+        &self,
+        grid_coords: &GridCoords,
+        time_state: &TimeState,
+        level_state: &LevelState,
+        player: &GridCoords,
+    ) -> bool {
+        self.impassable(grid_coords, time_state, level_state, player)
+            || self
+                .block_at(grid_coords, level_state, time_state)
+                .is_some()
+    }
+
+    /// Like [`Level::blocked`], but leaving out blocks, which the player can
+    /// push out of the way.
+    pub fn impassable(
+        &self,
+        grid_coords: &GridCoords,
+        time_state: &TimeState,
+        level_state: &LevelState,
+        player: &GridCoords,
+    ) -> bool {
+        self.in_wall(grid_coords)
+            || self.plant_blocks(grid_coords, time_state)
+            || self
+                .gate_at(grid_coords)
+                .is_some_and(|gate| self.gate_closed(gate, level_state, time_state, player))
+            || (self.water.contains(grid_coords) && time_state.current_seasion() != Seasion::Winter)
+            || self.echo_at(grid_coords, level_state, time_state, player)
+    }
+
+    pub fn orbs_at<'a>(&'a self, grid_coords: &'a GridCoords) -> impl Iterator<Item = &'a Orb> {
+        self.orbs
+            .iter()
+            .filter(move |orb| orb.grid_coords == *grid_coords)
+    }
+
+    pub fn goal_at(&self, grid_coords: &GridCoords) -> Option<&Goal> {
+        self.goals
+            .iter()
+            .find(|goal| goal.grid_coords == *grid_coords)
+    }
+
+    /// Whether standing on `grid_coords` at this time finishes the level.
+    pub fn goal_reached(&self, grid_coords: &GridCoords, time_state: &TimeState) -> bool {
+        self.goal_at(grid_coords)
+            .is_some_and(|goal| goal.accepts(time_state))
+    }
+}
+
+#[cfg(test)]
+impl Level {
+    /// A level of `width` by `height` tiles with nothing in it, for tests to
+    /// fill in. The player starts in the bottom left corner.
+    pub(crate) fn empty(width: i32, height: i32) -> Self {
+        Self {
+            identifier: "Test_level".to_string(),
+            walls: Walls::new(HashSet::new(), width, height),
+            ..default()
+        }
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use bevy_ecs_ldtk::ldtk::{EntityInstance, FieldInstance, FieldValue, LayerInstance};
+
+    use super::*;
+
+    /// Builds an LDtk level from rows of tiles, top row first: `#` for walls,
+    /// `~` for water, `^` for hazards, `P` for the player and `G` for a goal.
+    pub(crate) fn ldtk_level(rows: &[&str]) -> ldtk::Level {
+        let c_wid = rows[0].len() as i32;
+        let c_hei = rows.len() as i32;
+        let mut int_grid_csv = Vec::new();
+        let mut entity_instances = Vec::new();
+        for (y, row) in rows.iter().enumerate() {
+            for (x, tile) in row.chars().enumerate() {
+                int_grid_csv.push(match tile {
+                    '#' => WALL_INT_CELL,
+                    '~' => WATER_INT_CELL,
+                    '^' => HAZARD_INT_CELL,
+                    _ => 0,
+                });
+                let identifier = match tile {
+                    'P' => identifiers::PLAYER,
+                    'G' => identifiers::GOAL,
+                    _ => continue,
+                };
+                entity_instances.push(entity(identifier, x as i32, y as i32));
+            }
+        }
+
+        ldtk::Level {
+            identifier: "Test_level".to_string(),
+            px_wid: c_wid * GRID_SIZE,
+            px_hei: c_hei * GRID_SIZE,
+            layer_instances: Some(vec![
+                LayerInstance {
+                    identifier: "Entities".to_string(),
+                    layer_instance_type: Type::Entities,
+                    c_wid,
+                    c_hei,
+                    entity_instances,
+                    ..default()
+                },
+                LayerInstance {
+                    identifier: WALLS_LAYER.to_string(),
+                    layer_instance_type: Type::IntGrid,
+                    c_wid,
+                    c_hei,
+                    int_grid_csv,
+                    ..default()
+                },
+            ]),
+            ..default()
+        }
+    }
+
+    /// An entity at LDtk grid position `x`, `y`, counted from the top left.
+    pub(crate) fn entity(identifier: &str, x: i32, y: i32) -> EntityInstance {
+        EntityInstance {
+            identifier: identifier.to_string(),
+            grid: IVec2::new(x, y),
+            ..default()
+        }
+    }
+
+    /// `entity` with an extra field set.
+    pub(crate) fn with_field(
+        mut entity: EntityInstance,
+        identifier: &str,
+        value: FieldValue,
+    ) -> EntityInstance {
+        entity.field_instances.push(FieldInstance {
+            identifier: identifier.to_string(),
+            tile: None,
+            field_instance_type: String::new(),
+            value,
+            def_uid: 0,
+            real_editor_values: Vec::new(),
+        });
+        entity
+    }
+
+    /// Adds `entity` to the level's entity layer.
+    pub(crate) fn add_entity(level: &mut ldtk::Level, entity: EntityInstance) {
+        level
+            .layer_instances
+            .as_mut()
+            .expect("test levels have layers")
+            .iter_mut()
+            .find(|layer| layer.layer_instance_type == Type::Entities)
+            .expect("test levels have an entity layer")
+            .entity_instances
+            .push(entity);
+    }
+
+    #[test]
+    fn flips_ldtk_rows_into_grid_coords() {
+        let level =
+            Level::from_ldtk(&ldtk_level(&["#G~", "P.^"]), &OrbRegistry::default()).unwrap();
+
+        assert_eq!(level.player_start, GridCoords::new(0, 0));
+        assert_eq!(level.goals[0].grid_coords, GridCoords::new(1, 1));
+        assert!(level.in_wall(&GridCoords::new(0, 1)));
+        assert!(!level.in_wall(&GridCoords::new(1, 0)));
+        assert!(level.water.contains(&GridCoords::new(2, 1)));
+        assert!(level.hazards.contains(&GridCoords::new(2, 0)));
+    }
+
+    #[test]
+    fn outside_the_level_counts_as_wall() {
+        let level = Level::empty(2, 2);
+
+        assert!(!level.in_wall(&GridCoords::new(1, 1)));
+        assert!(level.in_wall(&GridCoords::new(-1, 0)));
+        assert!(level.in_wall(&GridCoords::new(0, 2)));
+    }
+
+    #[test]
+    fn needs_a_player() {
+        assert!(matches!(
+            Level::from_ldtk(&ldtk_level(&[".G"]), &OrbRegistry::default()),
+            Err(LevelError::MissingPlayer(_))
+        ));
+    }
+
+    #[test]
+    fn reads_orbs_from_the_registry() {
+        let mut ldtk_level = ldtk_level(&["P.."]);
+        add_entity(&mut ldtk_level, entity(identifiers::AXIS_SWITCH, 1, 0));
+        add_entity(&mut ldtk_level, entity("Not_an_orb", 2, 0));
+
+        let level = Level::from_ldtk(&ldtk_level, &OrbRegistry::default()).unwrap();
+
+        assert_eq!(level.orbs.len(), 1);
+        assert_eq!(level.orbs[0].grid_coords, GridCoords::new(1, 0));
+    }
+
+    #[test]
+    fn shows_the_identifier_without_a_name() {
+        let mut level = Level::empty(1, 1);
+        assert_eq!(level.display_name(), "Test level");
+
+        level.name = Some("Turning Time".to_string());
+        assert_eq!(level.display_name(), "Turning Time");
+    }
+}
@@ -0,0 +1,52 @@
+use std::collections::BTreeMap;
+
+use bevy::prelude::*;
+use bevy_ecs_ldtk::GridCoords;
+
+use super::{BlockState, Level};
+
+/// The parts of a level that change as it is played, other than the player
+/// and the clock. Kept in every [`super::Snapshot`], so undo and restart put
+/// them back too.
+#[derive(Resource, Debug, Default, Clone, PartialEq, Eq, Hash)]
+pub struct LevelState {
+    /// Charges left on each orb, in the order of [`Level::orbs`]. `None` for
+    /// orbs that never run out.
+    pub orb_charges: Vec<Option<u32>>,
+    /// The tiles the player stood on as the clock left each time, in levels
+    /// with echoes. Keyed by time, as echoes are looked up on every step.
+    pub footprints: BTreeMap<i32, Vec<GridCoords>>,
+    /// Where each block has been pushed to, in the order of
+    /// [`Level::blocks`].
+    pub blocks: Vec<BlockState>,
+}
+
+impl Level {
+    /// The state the level starts in.
+    pub fn initial_state(&self) -> LevelState {
+        LevelState {
+            orb_charges: self.orbs.iter().map(|orb| orb.charges).collect(),
+            footprints: BTreeMap::new(),
+            blocks: self
+                .blocks
+                .iter()
+                .map(|block| BlockState {
+                    grid_coords: block.start,
+                    melted: false,
+                })
+                .collect(),
+        }
+    }
+
+    /// Whether there are orbs at `grid_coords` and all of them have used up
+    /// their charges.
+    pub fn orb_spent(&self, level_state: &LevelState, grid_coords: &GridCoords) -> bool {
+        self.orbs
+            .iter()
+            .zip(&level_state.orb_charges)
+            .filter(|(orb, _)| orb.grid_coords == *grid_coords)
+            .map(|(_, charges)| *charges == Some(0))
+            .reduce(|spent, other_spent| spent && other_spent)
+            .unwrap_or(false)
+    }
+}
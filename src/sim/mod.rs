@@ -0,0 +1,46 @@
+//! A headless, deterministic model of the game rules.
+//!
+//! The Bevy plugins read input, hand it to these types and copy the results
+//! back onto entities, so puzzle rules can be exercised without an `App`.
+
+pub mod identifiers;
+
+mod block;
+mod death;
+mod echo;
+mod fields;
+mod gate;
+mod goal;
+mod history;
+mod level;
+mod level_state;
+mod orb;
+mod patrol;
+mod plant;
+mod project;
+mod replay;
+mod solver;
+mod time;
+mod validate;
+mod world;
+
+pub use block::{Block, BlockKind, BlockState};
+pub use death::DeathCause;
+pub use echo::PressurePlate;
+pub use gate::{Gate, GateSchedule};
+pub use goal::{Goal, Requirement};
+pub use history::{History, Snapshot};
+pub use level::{Level, LevelError, Walls, HAZARD_INT_CELL, WALL_INT_CELL, WATER_INT_CELL};
+pub use level_state::LevelState;
+pub use orb::{
+    AxisSwitch, DirectionSwitch, Freeze, Jump, Orb, OrbEffect, OrbRegistry, Recharge, RegisterOrb,
+    Reset, SlowDown, SpeedUp, StepChange, Unfreeze,
+};
+pub use patrol::Patrol;
+pub use plant::{Plant, PlantStage};
+pub use project::{load_project, ProjectError};
+pub use replay::{Input, Replay, ReplayError, REPLAY_VERSION};
+pub use solver::{solve, Solution, SolverResult};
+pub use time::{HourRange, Seasion, TimeAxis, TimeOnEnter, TimeState};
+pub use validate::{validate_level, Problem, Validation};
+pub use world::{Direction, MovementMode, Player, Preview, Step, StepOutcome, World};
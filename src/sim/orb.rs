@@ -0,0 +1,498 @@
+//! Orbs change the clock when the player walks into them. Each kind of orb is
+//! an [`OrbEffect`], registered in an [`OrbRegistry`] under the LDtk entity
+//! identifier levels place it with.
+
+use std::{fmt::Debug, sync::Arc};
+
+use bevy::{prelude::*, utils::HashMap};
+use bevy_ecs_ldtk::{ldtk::EntityInstance, GridCoords};
+
+use super::{fields, identifiers, LevelError, Seasion, TimeAxis, TimeState};
+
+/// Optional orb field naming the axis an `Axis_switch` sets, rather than
+/// swapping the axis over.
+const TARGET_AXIS_FIELD: &str = "Target_axis";
+
+/// Optional orb field giving how much a `Speed_up` or `Slow_down` changes
+/// the step by. Defaults to 1.
+const AMOUNT_FIELD: &str = "Amount";
+
+/// Optional orb field giving the step a `Speed_up` or `Slow_down` sets,
+/// whatever it was before. Takes precedence over `Amount`.
+const SET_DELTA_TO_FIELD: &str = "Set_delta_to";
+
+/// Optional orb field giving how many times the orb fires before it is used
+/// up. Orbs without it never run out.
+const CHARGES_FIELD: &str = "Charges";
+
+/// Optional orb fields giving when a used up orb gets its charges back.
+const RECHARGE_HOUR_FIELD: &str = "Recharge_hour";
+const RECHARGE_IN_FIELD: &str = "Recharge_in";
+
+/// Optional `Jump` field giving the hour it sets the clock to.
+const TO_HOUR_FIELD: &str = "To_hour";
+
+/// Optional `Jump` field giving the season it moves the clock into.
+const TO_SEASION_FIELD: &str = "To_season";
+
+/// What an orb does to the clock.
+pub trait OrbEffect: Debug + Send + Sync + 'static {
+    /// Reads the effect's settings from the fields on the orb's LDtk entity.
+    /// An orb with none of its fields set should behave sensibly.
+    fn from_ldtk(entity: &EntityInstance) -> Result<Self, LevelError>
+    where
+        Self: Sized;
+
+    fn apply(&self, time_state: &mut TimeState);
+
+    /// The colour of the light the orb gives off. Orbs that stop or move the
+    /// clock outright should stand out from the ones that change how it runs.
+    fn light_color(&self) -> Color {
+        Color::WHITE
+    }
+}
+
+/// Swaps which direction of movement runs the clock, or sets it to `target`.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct AxisSwitch {
+    pub target: Option<TimeAxis>,
+}
+
+impl OrbEffect for AxisSwitch {
+    fn from_ldtk(entity: &EntityInstance) -> Result<Self, LevelError> {
+        Ok(Self {
+            target: fields::maybe_time_axis(entity, TARGET_AXIS_FIELD)?,
+        })
+    }
+
+    fn apply(&self, time_state: &mut TimeState) {
+        time_state.time_axis = self.target.unwrap_or(match time_state.time_axis {
+            TimeAxis::Horizontal => TimeAxis::Vertical,
+            TimeAxis::Vertical => TimeAxis::Horizontal,
+            TimeAxis::None => TimeAxis::None,
+        })
+    }
+}
+
+/// Makes moves run the clock the other way.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct DirectionSwitch;
+
+impl OrbEffect for DirectionSwitch {
+    fn from_ldtk(_entity: &EntityInstance) -> Result<Self, LevelError> {
+        Ok(Self)
+    }
+
+    fn apply(&self, time_state: &mut TimeState) {
+        time_state.time_step_delta = -time_state.time_step_delta;
+    }
+}
+
+/// Changes how far each move runs the clock. Shared by `Speed_up` and
+/// `Slow_down`, which only differ in which way `Amount` counts.
+#[derive(Debug, Clone, Copy)]
+pub struct StepChange {
+    pub change: i32,
+    pub set_delta_to: Option<i32>,
+}
+
+impl StepChange {
+    fn from_ldtk(entity: &EntityInstance, sense: i32) -> Self {
+        Self {
+            change: sense * fields::maybe_int(entity, AMOUNT_FIELD).unwrap_or(1),
+            set_delta_to: fields::maybe_int(entity, SET_DELTA_TO_FIELD),
+        }
+    }
+
+    fn apply(&self, time_state: &mut TimeState) {
+        time_state.time_step_delta = self
+            .set_delta_to
+            .unwrap_or(time_state.time_step_delta + self.change);
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct SpeedUp(pub StepChange);
+
+impl OrbEffect for SpeedUp {
+    fn from_ldtk(entity: &EntityInstance) -> Result<Self, LevelError> {
+        Ok(Self(StepChange::from_ldtk(entity, 1)))
+    }
+
+    fn apply(&self, time_state: &mut TimeState) {
+        self.0.apply(time_state);
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct SlowDown(pub StepChange);
+
+impl OrbEffect for SlowDown {
+    fn from_ldtk(entity: &EntityInstance) -> Result<Self, LevelError> {
+        Ok(Self(StepChange::from_ldtk(entity, -1)))
+    }
+
+    fn apply(&self, time_state: &mut TimeState) {
+        self.0.apply(time_state);
+    }
+}
+
+/// Stops moves running the clock until an [`Unfreeze`] orb.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Freeze;
+
+impl OrbEffect for Freeze {
+    fn from_ldtk(_entity: &EntityInstance) -> Result<Self, LevelError> {
+        Ok(Self)
+    }
+
+    fn apply(&self, time_state: &mut TimeState) {
+        if time_state.time_axis != TimeAxis::None {
+            time_state.frozen_axis = Some(time_state.time_axis);
+            time_state.time_axis = TimeAxis::None;
+        }
+    }
+
+    fn light_color(&self) -> Color {
+        Color::srgb(0.55, 0.8, 1.0)
+    }
+}
+
+/// Puts back the axis a [`Freeze`] orb took away.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Unfreeze;
+
+impl OrbEffect for Unfreeze {
+    fn from_ldtk(_entity: &EntityInstance) -> Result<Self, LevelError> {
+        Ok(Self)
+    }
+
+    fn apply(&self, time_state: &mut TimeState) {
+        if let Some(axis) = time_state.frozen_axis.take() {
+            time_state.time_axis = axis;
+        }
+    }
+
+    fn light_color(&self) -> Color {
+        Color::srgb(1.0, 0.8, 0.3)
+    }
+}
+
+/// Puts the clock back to the time the level started at.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Reset;
+
+impl OrbEffect for Reset {
+    fn from_ldtk(_entity: &EntityInstance) -> Result<Self, LevelError> {
+        Ok(Self)
+    }
+
+    fn apply(&self, time_state: &mut TimeState) {
+        time_state.time = time_state.start_time;
+    }
+
+    fn light_color(&self) -> Color {
+        Color::srgb(0.5, 0.8, 0.35)
+    }
+}
+
+/// Sets the clock to a fixed hour, a fixed season or both.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Jump {
+    pub hour: Option<i32>,
+    pub seasion: Option<Seasion>,
+}
+
+impl OrbEffect for Jump {
+    fn from_ldtk(entity: &EntityInstance) -> Result<Self, LevelError> {
+        Ok(Self {
+            hour: fields::maybe_int(entity, TO_HOUR_FIELD),
+            seasion: fields::maybe_seasion(entity, TO_SEASION_FIELD)?,
+        })
+    }
+
+    fn apply(&self, time_state: &mut TimeState) {
+        time_state.jump_to(self.hour, self.seasion);
+    }
+
+    fn light_color(&self) -> Color {
+        Color::srgb(0.75, 0.25, 0.35)
+    }
+}
+
+/// When an orb gets its charges back: as the clock comes into the hour, the
+/// season, or that hour in that season.
+#[derive(Debug, Default, PartialEq, Eq, Copy, Clone, Hash)]
+pub struct Recharge {
+    pub hour: Option<i32>,
+    pub seasion: Option<Seasion>,
+}
+
+impl Recharge {
+    fn from_ldtk(entity: &EntityInstance) -> Result<Option<Self>, LevelError> {
+        let hour = fields::maybe_int(entity, RECHARGE_HOUR_FIELD);
+        let seasion = fields::maybe_seasion(entity, RECHARGE_IN_FIELD)?;
+        Ok((hour.is_some() || seasion.is_some()).then_some(Self { hour, seasion }))
+    }
+
+    pub fn is_due(&self, time_state: &TimeState) -> bool {
+        self.hour
+            .is_none_or(|hour| hour == time_state.current_hour())
+            && self
+                .seasion
+                .is_none_or(|seasion| seasion == time_state.current_seasion())
+    }
+}
+
+/// An orb placed in a level.
+#[derive(Debug, Clone)]
+pub struct Orb {
+    pub effect: Arc<dyn OrbEffect>,
+    pub grid_coords: GridCoords,
+    /// How many times the orb fires, or `None` if it never runs out.
+    pub charges: Option<u32>,
+    pub recharge: Option<Recharge>,
+}
+
+impl Orb {
+    pub fn from_ldtk(
+        entity: &EntityInstance,
+        grid_coords: GridCoords,
+        effect: Arc<dyn OrbEffect>,
+    ) -> Result<Self, LevelError> {
+        Ok(Self {
+            effect,
+            grid_coords,
+            charges: fields::maybe_int(entity, CHARGES_FIELD).map(|charges| charges.max(0) as u32),
+            recharge: Recharge::from_ldtk(entity)?,
+        })
+    }
+}
+
+type OrbConstructor = fn(&EntityInstance) -> Result<Arc<dyn OrbEffect>, LevelError>;
+
+fn construct<T: OrbEffect>(entity: &EntityInstance) -> Result<Arc<dyn OrbEffect>, LevelError> {
+    Ok(Arc::new(T::from_ldtk(entity)?))
+}
+
+/// The kinds of orb levels can use, by LDtk entity identifier. The default
+/// registry has the game's own orbs.
+#[derive(Resource, Debug, Clone)]
+pub struct OrbRegistry(HashMap<String, OrbConstructor>);
+
+impl Default for OrbRegistry {
+    fn default() -> Self {
+        let mut registry = Self(HashMap::new());
+        registry.register::<AxisSwitch>(identifiers::AXIS_SWITCH);
+        registry.register::<DirectionSwitch>(identifiers::DIRECTION_SWITCH);
+        registry.register::<SpeedUp>(identifiers::SPEED_UP);
+        registry.register::<SlowDown>(identifiers::SLOW_DOWN);
+        registry.register::<Freeze>(identifiers::FREEZE);
+        registry.register::<Unfreeze>(identifiers::UNFREEZE);
+        registry.register::<Reset>(identifiers::RESET);
+        registry.register::<Jump>(identifiers::JUMP);
+        registry
+    }
+}
+
+impl OrbRegistry {
+    /// Makes entities called `identifier` orbs with effect `T`, replacing any
+    /// orb already registered under that name.
+    pub fn register<T: OrbEffect>(&mut self, identifier: &str) {
+        self.0.insert(identifier.to_string(), construct::<T>);
+    }
+
+    pub fn contains(&self, identifier: &str) -> bool {
+        self.0.contains_key(identifier)
+    }
+
+    pub fn identifiers(&self) -> impl Iterator<Item = &str> {
+        self.0.keys().map(String::as_str)
+    }
+
+    /// The effect of `entity`, if it is an orb.
+    pub fn effect(
+        &self,
+        entity: &EntityInstance,
+    ) -> Result<Option<Arc<dyn OrbEffect>>, LevelError> {
+        self.0
+            .get(&entity.identifier)
+            .map(|construct| construct(entity))
+            .transpose()
+    }
+}
+
+/// Adds a kind of orb to the game, for plugins that bring their own.
+pub trait RegisterOrb {
+    /// Makes LDtk entities called `identifier` spawn as orbs that apply `T`
+    /// when the player walks into them. Register before the app starts, so
+    /// the orbs plugin sees it when it finishes.
+    fn register_orb<T: OrbEffect>(&mut self, identifier: &str) -> &mut Self;
+}
+
+impl RegisterOrb for App {
+    fn register_orb<T: OrbEffect>(&mut self, identifier: &str) -> &mut Self {
+        self.world_mut()
+            .get_resource_or_insert_with(OrbRegistry::default)
+            .register::<T>(identifier);
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy_ecs_ldtk::ldtk::FieldValue;
+
+    use super::*;
+    use crate::sim::level::tests::{entity, with_field};
+
+    fn applied(effect: impl OrbEffect, time_state: TimeState) -> TimeState {
+        let mut time_state = time_state;
+        effect.apply(&mut time_state);
+        time_state
+    }
+
+    fn from_fields<T: OrbEffect>(fields: &[(&str, FieldValue)]) -> T {
+        let entity = fields
+            .iter()
+            .fold(entity("Orb", 0, 0), |entity, (identifier, value)| {
+                with_field(entity, identifier, value.clone())
+            });
+        T::from_ldtk(&entity).unwrap()
+    }
+
+    #[test]
+    fn axis_switch_swaps_or_sets_the_axis() {
+        let swapped = applied(AxisSwitch::default(), TimeState::default());
+        assert_eq!(swapped.time_axis, TimeAxis::Vertical);
+        assert_eq!(
+            applied(AxisSwitch::default(), swapped).time_axis,
+            TimeAxis::Horizontal
+        );
+
+        let set: AxisSwitch = from_fields(&[(
+            TARGET_AXIS_FIELD,
+            FieldValue::Enum(Some("None".to_string())),
+        )]);
+        assert_eq!(applied(set, TimeState::default()).time_axis, TimeAxis::None);
+    }
+
+    #[test]
+    fn direction_switch_reverses_the_clock() {
+        assert_eq!(
+            applied(DirectionSwitch, TimeState::default()).time_step_delta,
+            -1
+        );
+    }
+
+    #[test]
+    fn speed_up_and_slow_down_change_the_step() {
+        let speed_up: SpeedUp = from_fields(&[(AMOUNT_FIELD, FieldValue::Int(Some(2)))]);
+        assert_eq!(applied(speed_up, TimeState::default()).time_step_delta, 3);
+
+        let slow_down: SlowDown = from_fields(&[]);
+        assert_eq!(applied(slow_down, TimeState::default()).time_step_delta, 0);
+
+        let set: SlowDown = from_fields(&[
+            (AMOUNT_FIELD, FieldValue::Int(Some(2))),
+            (SET_DELTA_TO_FIELD, FieldValue::Int(Some(5))),
+        ]);
+        assert_eq!(applied(set, TimeState::default()).time_step_delta, 5);
+    }
+
+    #[test]
+    fn unfreeze_puts_back_the_frozen_axis() {
+        let start = TimeState {
+            time_axis: TimeAxis::Vertical,
+            ..default()
+        };
+
+        let frozen = applied(Freeze, start);
+        assert_eq!(frozen.time_axis, TimeAxis::None);
+        // Freezing twice must not forget the axis.
+        let frozen = applied(Freeze, frozen);
+
+        assert_eq!(applied(Unfreeze, frozen), start);
+        assert_eq!(applied(Unfreeze, start), start);
+    }
+
+    #[test]
+    fn reset_goes_back_to_the_start_of_the_level() {
+        let time_state = TimeState {
+            time: 900,
+            start_time: 300,
+            ..default()
+        };
+
+        assert_eq!(applied(Reset, time_state).time, 300);
+    }
+
+    #[test]
+    fn jump_sets_the_hour_and_season() {
+        let jump: Jump = from_fields(&[
+            (TO_HOUR_FIELD, FieldValue::Int(Some(18))),
+            (
+                TO_SEASION_FIELD,
+                FieldValue::Enum(Some("Winter".to_string())),
+            ),
+        ]);
+
+        let time_state = applied(jump, TimeState::default());
+        assert_eq!(time_state.current_hour(), 18);
+        assert_eq!(time_state.current_seasion(), Seasion::Winter);
+    }
+
+    #[test]
+    fn rejects_unknown_enum_values() {
+        let entity = with_field(
+            entity("Orb", 0, 0),
+            TO_SEASION_FIELD,
+            FieldValue::Enum(Some("Monsoon".to_string())),
+        );
+
+        assert!(matches!(
+            Jump::from_ldtk(&entity),
+            Err(LevelError::UnknownSeasion(_, _))
+        ));
+    }
+
+    #[test]
+    fn recharges_at_the_hour_in_the_season() {
+        let recharge = Recharge {
+            hour: Some(6),
+            seasion: Some(Seasion::Summer),
+        };
+        let mut time_state = TimeState::default();
+        time_state.jump_to(Some(6), None);
+        assert!(!recharge.is_due(&time_state));
+
+        time_state.jump_to(None, Some(Seasion::Summer));
+        assert!(recharge.is_due(&time_state));
+    }
+
+    #[test]
+    fn orbs_without_charges_never_run_out() {
+        let orb = |entity| Orb::from_ldtk(&entity, GridCoords::default(), Arc::new(Reset)).unwrap();
+
+        assert_eq!(orb(entity("Reset", 0, 0)).charges, None);
+        assert_eq!(orb(entity("Reset", 0, 0)).recharge, None);
+        assert_eq!(
+            orb(with_field(
+                entity("Reset", 0, 0),
+                CHARGES_FIELD,
+                FieldValue::Int(Some(-1))
+            ))
+            .charges,
+            Some(0)
+        );
+    }
+
+    #[test]
+    fn the_registry_builds_registered_orbs() {
+        let mut registry = OrbRegistry::default();
+        assert!(registry.effect(&entity("Custom", 0, 0)).unwrap().is_none());
+
+        registry.register::<DirectionSwitch>("Custom");
+        assert!(registry.effect(&entity("Custom", 0, 0)).unwrap().is_some());
+    }
+}
@@ -0,0 +1,169 @@
+use bevy_ecs_ldtk::{
+    ldtk::{ldtk_fields::LdtkFields, EntityInstance},
+    utils::ldtk_grid_coords_to_grid_coords,
+    GridCoords,
+};
+
+use super::{fields, HourRange, TimeState};
+
+/// Optional point array field giving the tiles a patrol walks between, after
+/// the one it is placed on.
+const PATH_FIELD: &str = "Path";
+
+/// Optional hour fields giving when a patrol is out. Patrols without them
+/// come out at night.
+const ACTIVE_FROM_FIELD: &str = "Active_from";
+const ACTIVE_UNTIL_FIELD: &str = "Active_until";
+
+/// Optional field giving how many minutes on the clock a patrol takes to
+/// cross one tile. Defaults to 1.
+const MINUTES_PER_TILE_FIELD: &str = "Minutes_per_tile";
+
+/// When patrols are out unless they say otherwise.
+const NIGHT: HourRange = HourRange { from: 18, until: 6 };
+
+/// An enemy that walks back and forth along a path while it is out. Where it
+/// stands depends only on the clock, so running time backwards walks it back.
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+pub struct Patrol {
+    /// The LDtk iid of the entity, so the game can find its sprite.
+    pub iid: String,
+    /// Every tile along the way, in order, one step apart.
+    pub path: Vec<GridCoords>,
+    pub active: HourRange,
+    pub minutes_per_tile: i32,
+}
+
+impl Patrol {
+    /// Reads a patrol from its LDtk entity. `layer_height` is the height in
+    /// tiles of the layer it is on, for converting the path points.
+    pub fn from_ldtk(entity: &EntityInstance, grid_coords: GridCoords, layer_height: i32) -> Self {
+        let waypoints = entity
+            .get_maybe_points_field(PATH_FIELD)
+            .unwrap_or_default()
+            .iter()
+            .flatten()
+            .map(|point| ldtk_grid_coords_to_grid_coords(*point, layer_height));
+
+        let mut path = vec![grid_coords];
+        for waypoint in waypoints {
+            let mut tile = *path.last().expect("path starts with a tile");
+            while tile != waypoint {
+                // Walk along x first, then y, so diagonal waypoints still give
+                // a path of single steps.
+                if tile.x != waypoint.x {
+                    tile.x += (waypoint.x - tile.x).signum();
+                } else {
+                    tile.y += (waypoint.y - tile.y).signum();
+                }
+                path.push(tile);
+            }
+        }
+
+        Self {
+            iid: entity.iid.clone(),
+            path,
+            active: fields::maybe_hours(entity, ACTIVE_FROM_FIELD, ACTIVE_UNTIL_FIELD)
+                .unwrap_or(NIGHT),
+            minutes_per_tile: fields::maybe_int(entity, MINUTES_PER_TILE_FIELD)
+                .unwrap_or(1)
+                .max(1),
+        }
+    }
+
+    /// Where the patrol stands at this time, or `None` while it is not out.
+    pub fn position(&self, time_state: &TimeState) -> Option<GridCoords> {
+        if !self.active.contains(time_state.current_hour()) {
+            return None;
+        }
+
+        // There and back again, without lingering at either end.
+        let last = self.path.len() - 1;
+        if last == 0 {
+            return Some(self.path[0]);
+        }
+        let step = time_state
+            .time
+            .div_euclid(self.minutes_per_tile)
+            .rem_euclid(2 * last as i32) as usize;
+        Some(self.path[if step <= last { step } else { 2 * last - step }])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::IVec2;
+    use bevy_ecs_ldtk::ldtk::FieldValue;
+
+    use super::*;
+    use crate::sim::{
+        identifiers,
+        level::tests::{entity, with_field},
+    };
+
+    fn at(time: i32) -> TimeState {
+        TimeState {
+            time,
+            ..TimeState::default()
+        }
+    }
+
+    fn patrol(path: Vec<GridCoords>, minutes_per_tile: i32) -> Patrol {
+        Patrol {
+            iid: "patrol".to_string(),
+            path,
+            active: HourRange { from: 0, until: 24 },
+            minutes_per_tile,
+        }
+    }
+
+    #[test]
+    fn fills_in_the_path_between_waypoints() {
+        // LDtk points count down from the top of a five tile high layer.
+        let entity = with_field(
+            entity(identifiers::PATROL, 0, 4),
+            PATH_FIELD,
+            FieldValue::Points(vec![Some(IVec2::new(2, 3))]),
+        );
+        let patrol = Patrol::from_ldtk(&entity, GridCoords::new(0, 0), 5);
+
+        assert_eq!(
+            patrol.path,
+            vec![
+                GridCoords::new(0, 0),
+                GridCoords::new(1, 0),
+                GridCoords::new(2, 0),
+                GridCoords::new(2, 1),
+            ]
+        );
+        assert_eq!(patrol.active, NIGHT);
+        assert_eq!(patrol.minutes_per_tile, 1);
+    }
+
+    #[test]
+    fn walks_there_and_back() {
+        let path = vec![
+            GridCoords::new(0, 0),
+            GridCoords::new(1, 0),
+            GridCoords::new(2, 0),
+        ];
+        let patrol = patrol(path.clone(), 2);
+
+        let positions: Vec<_> = (0..10)
+            .step_by(2)
+            .map(|time| patrol.position(&at(time)).unwrap())
+            .collect();
+        assert_eq!(positions, vec![path[0], path[1], path[2], path[1], path[0]]);
+        // Running the clock back walks it back.
+        assert_eq!(patrol.position(&at(-2)), Some(path[1]));
+    }
+
+    #[test]
+    fn only_out_during_its_hours() {
+        let mut patrol = patrol(vec![GridCoords::new(0, 0)], 1);
+        patrol.active = NIGHT;
+
+        assert_eq!(patrol.position(&at(12 * 60)), None);
+        assert_eq!(patrol.position(&at(0)), Some(GridCoords::new(0, 0)));
+    }
+}
@@ -0,0 +1,144 @@
+use bevy_ecs_ldtk::{ldtk::EntityInstance, GridCoords};
+
+use super::{fields, Level, Seasion, TimeState};
+
+/// Optional plant field giving how many days each stage of growth lasts.
+/// Defaults to 1.
+const DAYS_PER_STAGE_FIELD: &str = "Days_per_stage";
+
+/// How far a plant has grown.
+#[derive(Debug, PartialEq, Eq, Copy, Clone, Hash)]
+pub enum PlantStage {
+    /// Just sprouted, and easily stepped over.
+    Seedling,
+    /// Thick enough to slow the player down.
+    Sapling,
+    /// Fully grown, and as good as a wall.
+    Tree,
+    /// A grown tree that has shed its leaves for Autumn, leaving room to
+    /// squeeze past.
+    Bare,
+}
+
+impl PlantStage {
+    pub fn blocks(self) -> bool {
+        self == PlantStage::Tree
+    }
+
+    pub fn slows(self) -> bool {
+        self == PlantStage::Sapling
+    }
+}
+
+/// A plant that grows as the days pass. Its stage depends only on the clock,
+/// so running time backwards shrinks it again.
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+pub struct Plant {
+    /// The LDtk iid of the entity, so the game can find its sprite.
+    pub iid: String,
+    pub grid_coords: GridCoords,
+    pub days_per_stage: i32,
+}
+
+impl Plant {
+    pub fn from_ldtk(entity: &EntityInstance, grid_coords: GridCoords) -> Self {
+        Self {
+            iid: entity.iid.clone(),
+            grid_coords,
+            days_per_stage: fields::maybe_int(entity, DAYS_PER_STAGE_FIELD)
+                .unwrap_or(1)
+                .max(1),
+        }
+    }
+
+    /// The plant's stage at this time. Plants start the level as seedlings.
+    pub fn stage(&self, time_state: &TimeState) -> PlantStage {
+        match time_state
+            .days_since_start()
+            .div_euclid(self.days_per_stage)
+        {
+            ..=0 => PlantStage::Seedling,
+            1 => PlantStage::Sapling,
+            _ if time_state.current_seasion() == Seasion::Autum => PlantStage::Bare,
+            _ => PlantStage::Tree,
+        }
+    }
+}
+
+impl Level {
+    /// Whether a grown tree stands on `grid_coords` at this time.
+    pub fn plant_blocks(&self, grid_coords: &GridCoords, time_state: &TimeState) -> bool {
+        self.plants_at(grid_coords)
+            .any(|plant| plant.stage(time_state).blocks())
+    }
+
+    /// Whether a sapling on `grid_coords` slows the player down at this time.
+    pub fn plant_slows(&self, grid_coords: &GridCoords, time_state: &TimeState) -> bool {
+        self.plants_at(grid_coords)
+            .any(|plant| plant.stage(time_state).slows())
+    }
+
+    fn plants_at<'a>(&'a self, grid_coords: &'a GridCoords) -> impl Iterator<Item = &'a Plant> {
+        self.plants
+            .iter()
+            .filter(move |plant| plant.grid_coords == *grid_coords)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sim::{Direction, Step, World};
+
+    const DAY: i32 = 24 * 60;
+
+    fn plant(days_per_stage: i32) -> Plant {
+        Plant {
+            iid: "plant".to_string(),
+            grid_coords: GridCoords::new(1, 0),
+            days_per_stage,
+        }
+    }
+
+    fn on_day(days: i32) -> TimeState {
+        TimeState {
+            time: days * DAY,
+            ..TimeState::default()
+        }
+    }
+
+    #[test]
+    fn grows_a_stage_every_few_days() {
+        let plant = plant(2);
+
+        assert_eq!(plant.stage(&on_day(-1)), PlantStage::Seedling);
+        assert_eq!(plant.stage(&on_day(1)), PlantStage::Seedling);
+        assert_eq!(plant.stage(&on_day(2)), PlantStage::Sapling);
+        assert_eq!(plant.stage(&on_day(8)), PlantStage::Tree);
+    }
+
+    #[test]
+    fn trees_are_bare_in_autumn() {
+        let mut time_state = on_day(2);
+        time_state.jump_to(None, Some(Seasion::Autum));
+
+        assert_eq!(plant(1).stage(&time_state), PlantStage::Bare);
+    }
+
+    #[test]
+    fn saplings_slow_grid_moves_and_trees_block_them() {
+        let mut world = World::new(Level {
+            plants: vec![plant(1)],
+            ..Level::empty(2, 1)
+        });
+
+        world.time_state = on_day(1);
+        let outcome = world.step(Step::Tile(Direction::Right));
+        assert!(outcome.slowed);
+        assert_eq!(world.time_state.time, DAY + 2);
+
+        let mut world = World::new(world.level);
+        world.time_state = on_day(2);
+        assert!(!world.step(Step::Tile(Direction::Right)).moved);
+    }
+}
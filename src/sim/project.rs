@@ -0,0 +1,20 @@
+use std::path::{Path, PathBuf};
+
+use bevy_ecs_ldtk::ldtk::LdtkJson;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum ProjectError {
+    #[error("could not read {0}: {1}")]
+    Io(PathBuf, std::io::Error),
+    #[error("could not parse {0}: {1}")]
+    Json(PathBuf, serde_json::Error),
+}
+
+/// Reads an LDtk project straight from disk, for tools that run without the
+/// asset server.
+pub fn load_project(path: impl AsRef<Path>) -> Result<LdtkJson, ProjectError> {
+    let path = path.as_ref();
+    let bytes = std::fs::read(path).map_err(|e| ProjectError::Io(path.to_owned(), e))?;
+    serde_json::from_slice(&bytes).map_err(|e| ProjectError::Json(path.to_owned(), e))
+}
@@ -0,0 +1,338 @@
+//! Recorded play sessions that can be played back exactly.
+//!
+//! A replay is JSON holding the level, the clock it started with and every
+//! input, written compactly as a string. Grid steps are `U`, `D`, `L` and
+//! `R`. Free steps are lower case, with a `!` in front when the step starts a
+//! new key press. `z` is an undo and `y` a redo. Any of these can be followed
+//! by a count to repeat it, so `!r30U2z` is a fresh press of right held for
+//! thirty frames, two grid steps up and an undo.
+
+use std::fmt::Write;
+
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::Value;
+use thiserror::Error;
+
+use super::{
+    Direction, History, Level, LevelState, Player, Snapshot, Step, StepOutcome, TimeState, World,
+};
+
+/// The version written by this build.
+pub const REPLAY_VERSION: u32 = 1;
+
+/// The most inputs a replay can hold, around five hours of free movement.
+/// Anything longer is taken to be corrupt rather than filling up memory.
+pub const MAX_REPLAY_INPUTS: usize = 1_000_000;
+
+#[derive(Debug, Error)]
+pub enum ReplayError {
+    #[error("replay is not valid: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("replay has no version")]
+    MissingVersion,
+    #[error("replay is version {0}, but this build only plays version {REPLAY_VERSION}")]
+    UnsupportedVersion(u32),
+}
+
+/// One thing the player did that changes the level.
+#[derive(Debug, PartialEq, Eq, Copy, Clone, Hash)]
+pub enum Input {
+    /// `new_move` is set when the step starts a move that can be undone on its
+    /// own. Grid steps always do, free steps only on a fresh key press.
+    Step {
+        step: Step,
+        new_move: bool,
+    },
+    Undo,
+    Redo,
+}
+
+impl Input {
+    /// Applies the input the way the game does, keeping `history` up to date.
+    /// For undo and redo, `moved` says whether there was anything to undo or
+    /// redo.
+    pub fn apply(
+        self,
+        level: &Level,
+        history: &mut History,
+        player: &mut Player,
+        time_state: &mut TimeState,
+        level_state: &mut LevelState,
+    ) -> StepOutcome {
+        // Free movement steps every frame, so the state is only copied for
+        // the steps that start a move.
+        let current = |level_state: &LevelState| Snapshot {
+            player: *player,
+            time_state: *time_state,
+            level_state: level_state.clone(),
+        };
+
+        let snapshot = match self {
+            Input::Step { step, new_move } => {
+                let before = new_move.then(|| current(level_state));
+                let outcome = level.step(player, time_state, level_state, step);
+                if let Some(before) = before.filter(|_| outcome.moved) {
+                    history.record(before);
+                }
+                return outcome;
+            }
+            Input::Undo => history.undo(current(level_state)),
+            Input::Redo => history.redo(current(level_state)),
+        };
+
+        let Some(snapshot) = snapshot else {
+            return StepOutcome::default();
+        };
+        *player = snapshot.player;
+        *time_state = snapshot.time_state;
+        *level_state = snapshot.level_state;
+        StepOutcome {
+            moved: true,
+            ..Default::default()
+        }
+    }
+
+    fn letter(self) -> (bool, char) {
+        let letter = |direction| match direction {
+            Direction::Up => 'u',
+            Direction::Down => 'd',
+            Direction::Left => 'l',
+            Direction::Right => 'r',
+        };
+
+        match self {
+            Input::Step {
+                step: Step::Tile(direction),
+                ..
+            } => (false, letter(direction).to_ascii_uppercase()),
+            Input::Step {
+                step: Step::Free(direction),
+                new_move,
+            } => (new_move, letter(direction)),
+            Input::Undo => (false, 'z'),
+            Input::Redo => (false, 'y'),
+        }
+    }
+
+    fn from_letter(new_move: bool, letter: char) -> Option<Self> {
+        let direction = match letter.to_ascii_lowercase() {
+            'u' => Direction::Up,
+            'd' => Direction::Down,
+            'l' => Direction::Left,
+            'r' => Direction::Right,
+            'z' if !new_move => return Some(Input::Undo),
+            'y' if !new_move => return Some(Input::Redo),
+            _ => return None,
+        };
+
+        if letter.is_ascii_uppercase() {
+            (!new_move).then_some(Input::Step {
+                step: Step::Tile(direction),
+                new_move: true,
+            })
+        } else {
+            Some(Input::Step {
+                step: Step::Free(direction),
+                new_move,
+            })
+        }
+    }
+}
+
+fn encode_inputs(inputs: &[Input]) -> String {
+    let mut encoded = String::new();
+    let mut index = 0;
+    while index < inputs.len() {
+        let input = inputs[index];
+        let count = inputs[index..]
+            .iter()
+            .take_while(|other| **other == input)
+            .count();
+
+        let (new_move, letter) = input.letter();
+        if new_move {
+            encoded.push('!');
+        }
+        encoded.push(letter);
+        if count > 1 {
+            write!(encoded, "{count}").expect("writing to a String can't fail");
+        }
+        index += count;
+    }
+    encoded
+}
+
+fn decode_inputs(encoded: &str) -> Result<Vec<Input>, String> {
+    let mut inputs = Vec::new();
+    let mut chars = encoded.chars().peekable();
+    while let Some(mut letter) = chars.next() {
+        let new_move = letter == '!';
+        if new_move {
+            letter = chars.next().ok_or("replay inputs end after a !")?;
+        }
+        let input = Input::from_letter(new_move, letter)
+            .ok_or_else(|| format!("unknown replay input {letter:?}"))?;
+
+        let mut count = String::new();
+        while let Some(digit) = chars.next_if(char::is_ascii_digit) {
+            count.push(digit);
+        }
+        let count = if count.is_empty() {
+            1
+        } else {
+            count
+                .parse()
+                .map_err(|_| format!("bad repeat count {count}"))?
+        };
+
+        if count > MAX_REPLAY_INPUTS - inputs.len() {
+            return Err(format!("replay has more than {MAX_REPLAY_INPUTS} inputs"));
+        }
+        inputs.extend(std::iter::repeat_n(input, count));
+    }
+    Ok(inputs)
+}
+
+fn serialize_inputs<S: Serializer>(inputs: &[Input], serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&encode_inputs(inputs))
+}
+
+fn deserialize_inputs<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<Input>, D::Error> {
+    decode_inputs(&String::deserialize(deserializer)?).map_err(de::Error::custom)
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Replay {
+    pub version: u32,
+    pub level_iid: String,
+    /// The clock when the recording started.
+    pub start: TimeState,
+    #[serde(
+        serialize_with = "serialize_inputs",
+        deserialize_with = "deserialize_inputs"
+    )]
+    pub inputs: Vec<Input>,
+}
+
+impl Replay {
+    pub fn new(level_iid: String, start: TimeState) -> Self {
+        Self {
+            version: REPLAY_VERSION,
+            level_iid,
+            start,
+            inputs: Vec::new(),
+        }
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, ReplayError> {
+        let value: Value = serde_json::from_str(json)?;
+        let version = value
+            .get("version")
+            .and_then(Value::as_u64)
+            .ok_or(ReplayError::MissingVersion)? as u32;
+        if version != REPLAY_VERSION {
+            return Err(ReplayError::UnsupportedVersion(version));
+        }
+
+        Ok(serde_json::from_value(value)?)
+    }
+
+    pub fn to_json(&self) -> Result<String, ReplayError> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    /// Plays every input on `level` from the start, without a running game.
+    pub fn play(&self, level: Level) -> World {
+        let mut world = World::new(level);
+        world.time_state = self.start;
+        let mut history = History::default();
+        for input in &self.inputs {
+            input.apply(
+                &world.level,
+                &mut history,
+                &mut world.player,
+                &mut world.time_state,
+                &mut world.level_state,
+            );
+        }
+        world
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn free(direction: Direction, new_move: bool) -> Input {
+        Input::Step {
+            step: Step::Free(direction),
+            new_move,
+        }
+    }
+
+    fn tile(direction: Direction) -> Input {
+        Input::Step {
+            step: Step::Tile(direction),
+            new_move: true,
+        }
+    }
+
+    #[test]
+    fn encodes_runs_compactly() {
+        let mut inputs = vec![free(Direction::Right, true)];
+        inputs.extend(std::iter::repeat_n(free(Direction::Right, false), 29));
+        inputs.extend([tile(Direction::Up), tile(Direction::Up)]);
+        inputs.extend([Input::Undo, Input::Redo]);
+
+        assert_eq!(encode_inputs(&inputs), "!rr29U2zy");
+    }
+
+    #[test]
+    fn decodes_what_it_encodes() {
+        let mut inputs = Vec::new();
+        for direction in Direction::ALL {
+            inputs.push(free(direction, true));
+            inputs.extend(std::iter::repeat_n(free(direction, false), 12));
+            inputs.push(tile(direction));
+        }
+        inputs.extend([Input::Undo, Input::Undo, Input::Redo]);
+
+        assert_eq!(decode_inputs(&encode_inputs(&inputs)), Ok(inputs));
+    }
+
+    #[test]
+    fn replay_survives_json() {
+        let mut replay = Replay::new("level-iid".to_string(), TimeState::default());
+        replay.inputs = vec![free(Direction::Left, true), tile(Direction::Down)];
+
+        let json = replay.to_json().unwrap();
+        assert_eq!(Replay::from_json(&json).unwrap(), replay);
+    }
+
+    #[test]
+    fn rejects_unknown_inputs() {
+        assert!(decode_inputs("!z").is_err());
+        assert!(decode_inputs("!U").is_err());
+        assert!(decode_inputs("x").is_err());
+        assert!(decode_inputs("r!").is_err());
+    }
+
+    #[test]
+    fn rejects_huge_repeat_counts() {
+        assert!(decode_inputs("r99999999999").is_err());
+        assert!(decode_inputs(&format!("r{MAX_REPLAY_INPUTS}")).is_ok());
+        assert!(decode_inputs(&format!("U{MAX_REPLAY_INPUTS}D")).is_err());
+    }
+
+    #[test]
+    fn rejects_other_versions() {
+        assert!(matches!(
+            Replay::from_json(r#"{"version": 2}"#),
+            Err(ReplayError::UnsupportedVersion(2))
+        ));
+        assert!(matches!(
+            Replay::from_json("{}"),
+            Err(ReplayError::MissingVersion)
+        ));
+    }
+}
@@ -0,0 +1,260 @@
+use std::collections::VecDeque;
+
+use bevy::{prelude::IVec2, utils::HashSet};
+use bevy_ecs_ldtk::{utils::grid_coords_to_translation, GridCoords};
+
+use super::{Direction, Level, LevelState, MovementMode, Player, Step, StepOutcome, TimeState};
+use crate::consts;
+
+/// A shortest route through a level.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Solution {
+    /// One tile per entry.
+    pub moves: Vec<Direction>,
+    /// The clock when the player reaches the goal.
+    pub time_state: TimeState,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SolverResult {
+    Solved(Solution),
+    /// Every reachable state was explored without finding the goal.
+    Unsolvable,
+    /// The search hit its state limit before reaching an answer.
+    GaveUp,
+}
+
+/// The parts of a search node that decide where the player can go next.
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+struct StateKey {
+    grid_coords: GridCoords,
+    in_orb: bool,
+    time_state: TimeState,
+    level_state: LevelState,
+}
+
+struct Node {
+    player: Player,
+    time_state: TimeState,
+    level_state: LevelState,
+    parent: Option<(usize, Direction)>,
+}
+
+impl Node {
+    fn key(&self) -> StateKey {
+        StateKey {
+            grid_coords: self.player.grid_coords,
+            in_orb: self.player.in_orb,
+            time_state: self.time_state,
+            level_state: self.level_state.clone(),
+        }
+    }
+}
+
+/// Breadth first search over moves from tile to tile, so the first solution
+/// found uses as few moves as possible. The clock starts the way the game
+/// starts it for a player arriving with `arriving`. In free movement each
+/// move glides from the middle of one tile to the middle of the next, so the
+/// clock runs for every pixel walked, as it does in play; routes that stop
+/// part way across a tile aren't tried. Gives up after visiting `max_states`
+/// states.
+pub fn solve(
+    level: &Level,
+    arriving: TimeState,
+    mode: MovementMode,
+    max_states: usize,
+) -> SolverResult {
+    let start = Node {
+        player: Player::at(level.player_start),
+        time_state: level.time_on_enter.apply(arriving),
+        level_state: level.initial_state(),
+        parent: None,
+    };
+
+    let mut visited = HashSet::new();
+    visited.insert(start.key());
+    let mut nodes = vec![start];
+    let mut queue = VecDeque::from([0]);
+
+    while let Some(index) = queue.pop_front() {
+        for direction in Direction::ALL {
+            let mut player = nodes[index].player;
+            let mut time_state = nodes[index].time_state;
+            let mut level_state = nodes[index].level_state.clone();
+            let outcome = match mode {
+                MovementMode::Grid => level.step(
+                    &mut player,
+                    &mut time_state,
+                    &mut level_state,
+                    Step::Tile(direction),
+                ),
+                MovementMode::Free => glide(
+                    level,
+                    &mut player,
+                    &mut time_state,
+                    &mut level_state,
+                    direction,
+                ),
+            };
+            if !outcome.moved || outcome.died.is_some() {
+                continue;
+            }
+
+            let node = Node {
+                player,
+                time_state,
+                level_state,
+                parent: Some((index, direction)),
+            };
+
+            if outcome.reached_goal {
+                nodes.push(node);
+                return SolverResult::Solved(Solution {
+                    moves: moves_to(&nodes, nodes.len() - 1),
+                    time_state,
+                });
+            }
+
+            if visited.insert(node.key()) {
+                if visited.len() > max_states {
+                    return SolverResult::GaveUp;
+                }
+                queue.push_back(nodes.len());
+                nodes.push(node);
+            }
+        }
+    }
+
+    SolverResult::Unsolvable
+}
+
+/// Takes free movement steps in `direction` until the player stands in the
+/// middle of the next tile, stopping early if they die or reach the goal on
+/// the way.
+/// The outcome is for the last step, except that it only counts as a move if
+/// the player got off their tile.
+fn glide(
+    level: &Level,
+    player: &mut Player,
+    time_state: &mut TimeState,
+    level_state: &mut LevelState,
+    direction: Direction,
+) -> StepOutcome {
+    let target = player.grid_coords + GridCoords::from(direction.offset());
+    let centre = grid_coords_to_translation(target, IVec2::splat(consts::GRID_SIZE));
+    // Half speed through a sapling, across the whole of both tiles.
+    let max_steps = (2.0 * consts::GRID_SIZE as f64
+        / (consts::MOVEMENT_SPEED * consts::SAPLING_SPEED_FACTOR))
+        .ceil() as usize;
+
+    let mut outcome = StepOutcome::default();
+    for _ in 0..max_steps {
+        outcome = level.step(player, time_state, level_state, Step::Free(direction));
+        if !outcome.moved
+            || outcome.died.is_some()
+            || outcome.reached_goal
+            || player.translation == centre
+        {
+            break;
+        }
+    }
+    outcome.moved &= player.grid_coords == target;
+    outcome
+}
+
+fn moves_to(nodes: &[Node], mut index: usize) -> Vec<Direction> {
+    let mut moves = Vec::new();
+    while let Some((parent, direction)) = nodes[index].parent {
+        moves.push(direction);
+        index = parent;
+    }
+    moves.reverse();
+    moves
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sim::{Goal, TimeOnEnter, Walls};
+
+    fn level_with_goal(width: i32, height: i32, goal: GridCoords) -> Level {
+        Level {
+            goals: vec![Goal {
+                grid_coords: goal,
+                requirements: Vec::new(),
+            }],
+            ..Level::empty(width, height)
+        }
+    }
+
+    #[test]
+    fn finds_a_shortest_route() {
+        let level = level_with_goal(3, 2, GridCoords::new(2, 1));
+
+        for mode in MovementMode::ALL {
+            let SolverResult::Solved(solution) = solve(&level, TimeState::default(), mode, 100)
+            else {
+                panic!("no route with {mode:?} movement");
+            };
+            assert_eq!(solution.moves.len(), 3);
+        }
+    }
+
+    #[test]
+    fn times_free_movement_by_the_pixel() {
+        let level = level_with_goal(2, 1, GridCoords::new(1, 0));
+
+        let SolverResult::Solved(solution) =
+            solve(&level, TimeState::default(), MovementMode::Free, 100)
+        else {
+            panic!("no route");
+        };
+        // Reached as soon as the player crosses into the goal's tile.
+        assert_eq!(solution.time_state.time, consts::GRID_SIZE / 2);
+    }
+
+    #[test]
+    fn starts_from_the_arriving_clock_when_the_level_keeps_it() {
+        let mut level = level_with_goal(2, 1, GridCoords::new(1, 0));
+        let arriving = TimeState {
+            time: 100,
+            ..TimeState::default()
+        };
+
+        let SolverResult::Solved(solution) = solve(&level, arriving, MovementMode::Grid, 100)
+        else {
+            panic!("no route");
+        };
+        assert_eq!(solution.time_state.time, 1);
+
+        level.time_on_enter = TimeOnEnter::Keep;
+        let SolverResult::Solved(solution) = solve(&level, arriving, MovementMode::Grid, 100)
+        else {
+            panic!("no route");
+        };
+        assert_eq!(solution.time_state.time, 101);
+    }
+
+    #[test]
+    fn knows_when_there_is_no_route() {
+        let level = Level {
+            walls: Walls::new(HashSet::from_iter([GridCoords::new(1, 0)]), 3, 1),
+            ..level_with_goal(3, 1, GridCoords::new(2, 0))
+        };
+
+        assert_eq!(
+            solve(&level, TimeState::default(), MovementMode::Grid, 100),
+            SolverResult::Unsolvable
+        );
+    }
+
+    #[test]
+    fn gives_up_after_too_many_states() {
+        let level = level_with_goal(10, 10, GridCoords::new(9, 9));
+
+        assert_eq!(
+            solve(&level, TimeState::default(), MovementMode::Grid, 10),
+            SolverResult::GaveUp
+        );
+    }
+}
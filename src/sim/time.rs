@@ -0,0 +1,365 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::consts::DAYS_PER_SEASION;
+
+const MINUTES_PER_DAY: i32 = 24 * 60;
+const MINUTES_PER_SEASION: i32 = DAYS_PER_SEASION * MINUTES_PER_DAY;
+
+#[derive(Default, Debug, PartialEq, Eq, Copy, Clone, Hash, Serialize, Deserialize)]
+pub enum TimeAxis {
+    #[default]
+    Horizontal,
+    Vertical,
+    None,
+}
+
+impl TimeAxis {
+    /// Maps an LDtk `Time_axis` enum value to an axis.
+    pub fn from_identifier(identifier: &str) -> Option<Self> {
+        match identifier {
+            "Horizontal" => Some(TimeAxis::Horizontal),
+            "Vertical" => Some(TimeAxis::Vertical),
+            "None" => Some(TimeAxis::None),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for TimeAxis {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&format!("{self:?}"))
+    }
+}
+
+#[derive(Resource, Debug, Clone, Copy, Eq, PartialEq, Hash, Serialize, Deserialize)]
+pub struct TimeState {
+    pub time_axis: TimeAxis,
+    pub time_step_delta: i32,
+    pub time: i32,
+    /// The axis to go back to once time unfreezes. Only set while
+    /// `time_axis` is `None` because of a freeze.
+    #[serde(default)]
+    pub frozen_axis: Option<TimeAxis>,
+    /// The time the level started at, for putting the clock back.
+    #[serde(default)]
+    pub start_time: i32,
+}
+
+impl Default for TimeState {
+    fn default() -> Self {
+        Self {
+            time_axis: TimeAxis::Horizontal,
+            time_step_delta: 1,
+            time: 0,
+            frozen_axis: None,
+            start_time: 0,
+        }
+    }
+}
+
+/// What happens to the clock when the player arrives in a level.
+#[derive(Default, Debug, PartialEq, Eq, Copy, Clone, Hash)]
+pub enum TimeOnEnter {
+    /// Carry on from the time the previous level finished at.
+    Keep,
+    /// Start again from the default time.
+    #[default]
+    Reset,
+}
+
+impl TimeOnEnter {
+    /// Maps the value of a level's `Time_on_enter` field.
+    pub fn from_identifier(identifier: &str) -> Option<Self> {
+        match identifier {
+            "Keep" => Some(TimeOnEnter::Keep),
+            "Reset" => Some(TimeOnEnter::Reset),
+            _ => None,
+        }
+    }
+
+    /// The clock to start the level with, given the clock the player arrives
+    /// with.
+    pub fn apply(self, arriving: TimeState) -> TimeState {
+        match self {
+            TimeOnEnter::Keep => TimeState {
+                start_time: arriving.time,
+                ..arriving
+            },
+            TimeOnEnter::Reset => TimeState::default(),
+        }
+    }
+}
+
+/// The hours from `from` up to, but not including, `until`. Ranges wrap past
+/// midnight, so 18 to 6 covers the night.
+#[derive(Debug, PartialEq, Eq, Copy, Clone, Hash)]
+pub struct HourRange {
+    pub from: i32,
+    pub until: i32,
+}
+
+impl HourRange {
+    pub fn contains(&self, hour: i32) -> bool {
+        if self.from <= self.until {
+            self.from <= hour && hour < self.until
+        } else {
+            hour >= self.from || hour < self.until
+        }
+    }
+}
+
+impl std::fmt::Display for HourRange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:00 to {}:00", self.from, self.until)
+    }
+}
+
+#[derive(Default, Debug, PartialEq, Eq, Copy, Clone, Hash)]
+pub enum Seasion {
+    #[default]
+    Spring,
+    Summer,
+    Autum,
+    Winter,
+}
+
+impl Seasion {
+    pub const ALL: [Seasion; 4] = [
+        Seasion::Spring,
+        Seasion::Summer,
+        Seasion::Autum,
+        Seasion::Winter,
+    ];
+
+    /// Maps an LDtk `Season` enum value to a season.
+    pub fn from_identifier(identifier: &str) -> Option<Self> {
+        match identifier {
+            "Spring" => Some(Seasion::Spring),
+            "Summer" => Some(Seasion::Summer),
+            "Autumn" | "Autum" => Some(Seasion::Autum),
+            "Winter" => Some(Seasion::Winter),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for Seasion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&format!("{self:?}"))
+    }
+}
+
+impl TimeState {
+    /// How far the clock is from `start`, in minutes, whichever way it ran.
+    pub fn minutes_since(&self, start: &TimeState) -> u32 {
+        self.time.abs_diff(start.time)
+    }
+
+    pub fn current_hour(&self) -> i32 {
+        self.time.div_euclid(60).rem_euclid(24)
+    }
+
+    /// How light it is, from 0 at midnight to 1 at noon.
+    pub fn daylight(&self) -> f32 {
+        let hour = self.current_hour() as f32;
+        0.5 * (((hour - 12.0) * std::f32::consts::TAU / 24.0).cos() + 1.0)
+    }
+
+    /// The day of the level the clock is on, counting from day 1.
+    pub fn day(&self) -> i32 {
+        self.time.div_euclid(MINUTES_PER_DAY) + 1
+    }
+
+    /// Whole days since the level started, negative if the clock has run back
+    /// past the start.
+    pub fn days_since_start(&self) -> i32 {
+        (self.time - self.start_time).div_euclid(MINUTES_PER_DAY)
+    }
+
+    pub fn current_seasion(&self) -> Seasion {
+        // Euclidean division keeps the calendar running backwards once the
+        // clock goes negative.
+        let seasion_int = self.time.div_euclid(MINUTES_PER_SEASION).rem_euclid(4);
+
+        match seasion_int {
+            0 => Seasion::Spring,
+            1 => Seasion::Summer,
+            2 => Seasion::Autum,
+            3 => Seasion::Winter,
+            _ => unreachable!("This should never happen"),
+        }
+    }
+
+    /// The season the clock was in just before it came into its current one,
+    /// on its way here from `before`, or `None` if it hasn't left the season
+    /// it was in then. The clock may have jumped, so this isn't always the
+    /// season before this one in the calendar.
+    pub fn seasion_passed_from(&self, before: &TimeState) -> Option<Seasion> {
+        let here = self.time.div_euclid(MINUTES_PER_SEASION);
+        let there = before.time.div_euclid(MINUTES_PER_SEASION);
+        let passed = match here.cmp(&there) {
+            std::cmp::Ordering::Equal => return None,
+            std::cmp::Ordering::Greater => here - 1,
+            std::cmp::Ordering::Less => here + 1,
+        };
+        Some(Seasion::ALL[passed.rem_euclid(4) as usize])
+    }
+
+    /// Sets the clock to `hour` on the current day and moves it to the same
+    /// point in `seasion` of the current year. Either can be left as it is.
+    pub fn jump_to(&mut self, hour: Option<i32>, seasion: Option<Seasion>) {
+        if let Some(seasion) = seasion {
+            let year_start =
+                self.time.div_euclid(MINUTES_PER_SEASION * 4) * MINUTES_PER_SEASION * 4;
+            let seasion_index = Seasion::ALL
+                .iter()
+                .position(|other| *other == seasion)
+                .expect("every season is in Seasion::ALL") as i32;
+            self.time = year_start
+                + seasion_index * MINUTES_PER_SEASION
+                + self.time.rem_euclid(MINUTES_PER_SEASION);
+        }
+        if let Some(hour) = hour {
+            self.time =
+                self.time.div_euclid(MINUTES_PER_DAY) * MINUTES_PER_DAY + hour.rem_euclid(24) * 60;
+        }
+    }
+
+    /// Moves the clock for one step taken along `axis` in the direction given
+    /// by `sense`. Steps along the other axis leave the clock alone.
+    pub fn advance(&mut self, axis: TimeAxis, sense: i32) {
+        if self.time_axis == axis {
+            self.time += sense * self.time_step_delta;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(time: i32) -> TimeState {
+        TimeState { time, ..default() }
+    }
+
+    #[test]
+    fn only_moves_along_the_axis_run_the_clock() {
+        let mut time_state = TimeState {
+            time_step_delta: 3,
+            ..default()
+        };
+
+        time_state.advance(TimeAxis::Horizontal, 1);
+        time_state.advance(TimeAxis::Vertical, 1);
+        assert_eq!(time_state.time, 3);
+
+        time_state.advance(TimeAxis::Horizontal, -1);
+        time_state.advance(TimeAxis::Horizontal, -1);
+        assert_eq!(time_state.time, -3);
+
+        time_state.time_axis = TimeAxis::None;
+        time_state.advance(TimeAxis::Horizontal, 1);
+        assert_eq!(time_state.time, -3);
+    }
+
+    #[test]
+    fn the_calendar_runs_backwards_too() {
+        let minutes_per_seasion = DAYS_PER_SEASION * 24 * 60;
+        let at = |time| TimeState { time, ..default() };
+
+        assert_eq!(at(0).current_hour(), 0);
+        assert_eq!(at(25 * 60).current_hour(), 1);
+        assert_eq!(at(-1).current_hour(), 23);
+
+        assert_eq!(at(0).current_seasion(), Seasion::Spring);
+        assert_eq!(at(minutes_per_seasion).current_seasion(), Seasion::Summer);
+        assert_eq!(at(-1).current_seasion(), Seasion::Winter);
+        assert_eq!(
+            at(4 * minutes_per_seasion).current_seasion(),
+            Seasion::Spring
+        );
+    }
+
+    #[test]
+    fn counts_days_from_the_start_of_the_level() {
+        let time_state = TimeOnEnter::Keep.apply(at(MINUTES_PER_DAY / 2));
+
+        assert_eq!(time_state.days_since_start(), 0);
+        assert_eq!(
+            TimeState {
+                time: time_state.time + MINUTES_PER_DAY,
+                ..time_state
+            }
+            .days_since_start(),
+            1
+        );
+        assert_eq!(
+            TimeState {
+                time: time_state.time - 1,
+                ..time_state
+            }
+            .days_since_start(),
+            -1
+        );
+    }
+
+    #[test]
+    fn keep_carries_the_clock_over_and_reset_does_not() {
+        let arriving = TimeState {
+            time_axis: TimeAxis::Vertical,
+            time: 500,
+            ..default()
+        };
+
+        let kept = TimeOnEnter::Keep.apply(arriving);
+        assert_eq!(kept.time, 500);
+        assert_eq!(kept.start_time, 500);
+        assert_eq!(kept.time_axis, TimeAxis::Vertical);
+
+        assert_eq!(TimeOnEnter::Reset.apply(arriving), TimeState::default());
+    }
+
+    #[test]
+    fn jumps_within_the_day_and_the_year() {
+        let mut time_state = at(MINUTES_PER_DAY + 90);
+        time_state.jump_to(Some(6), None);
+        assert_eq!(time_state.time, MINUTES_PER_DAY + 6 * 60);
+
+        time_state.jump_to(None, Some(Seasion::Autum));
+        assert_eq!(time_state.current_seasion(), Seasion::Autum);
+        assert_eq!(time_state.current_hour(), 6);
+        assert_eq!(
+            time_state.time,
+            2 * MINUTES_PER_SEASION + MINUTES_PER_DAY + 6 * 60
+        );
+
+        time_state.jump_to(Some(0), Some(Seasion::Spring));
+        assert_eq!(time_state.time, MINUTES_PER_DAY);
+    }
+
+    #[test]
+    fn knows_the_season_it_just_left() {
+        let spring = at(0);
+        let summer = at(MINUTES_PER_SEASION);
+        let autumn = at(2 * MINUTES_PER_SEASION);
+
+        assert_eq!(spring.seasion_passed_from(&at(1)), None);
+        assert_eq!(autumn.seasion_passed_from(&spring), Some(Seasion::Summer));
+        assert_eq!(summer.seasion_passed_from(&autumn), Some(Seasion::Autum));
+        assert_eq!(at(-1).seasion_passed_from(&spring), Some(Seasion::Spring));
+    }
+
+    #[test]
+    fn hour_ranges_wrap_past_midnight() {
+        let night = HourRange { from: 18, until: 6 };
+        assert!(night.contains(23));
+        assert!(night.contains(0));
+        assert!(!night.contains(6));
+        assert!(!night.contains(12));
+
+        let day = HourRange { from: 6, until: 18 };
+        assert!(day.contains(6));
+        assert!(!day.contains(18));
+    }
+}
@@ -0,0 +1,279 @@
+use bevy::prelude::*;
+use bevy_ecs_ldtk::ldtk::{self, Type};
+use thiserror::Error;
+
+use super::{
+    identifiers, level::WALLS_LAYER, solve, Level, LevelError, MovementMode, OrbRegistry,
+    SolverResult, TimeState, WALL_INT_CELL,
+};
+
+/// A mistake in a level that would otherwise only show up in play. Grid
+/// positions are LDtk's, counted from the top left.
+#[derive(Debug, Error)]
+pub enum Problem {
+    #[error("no Player entity")]
+    MissingPlayer,
+    #[error("{0} Player entities, expected exactly one")]
+    DuplicatePlayer(usize),
+    #[error("no Goal entity")]
+    MissingGoal,
+    #[error("{identifier} at {grid} is not registered with the game and won't spawn")]
+    UnregisteredEntity { identifier: String, grid: IVec2 },
+    #[error("{identifier} at {grid} is inside a wall")]
+    EntityInWall { identifier: String, grid: IVec2 },
+    #[error("the goal can't be reached with {mode:?} movement starting at time {start}")]
+    GoalUnreachable { mode: MovementMode, start: i32 },
+    #[error(
+        "gave up looking for a route to the goal with {mode:?} movement starting at time \
+         {start} after {max_states} states"
+    )]
+    SolverGaveUp {
+        mode: MovementMode,
+        start: i32,
+        max_states: usize,
+    },
+    #[error(transparent)]
+    Invalid(#[from] LevelError),
+}
+
+/// What [`validate_level`] found out about a level.
+#[derive(Debug, Default)]
+pub struct Validation {
+    pub problems: Vec<Problem>,
+    /// The clocks at the goal on the shortest route found in each movement
+    /// mode from each start, without repeats, for the next level to start
+    /// from.
+    pub finishes: Vec<TimeState>,
+}
+
+/// Checks a level for everything the game assumes about it, including that
+/// the solver can find a way to the goal in every movement mode the level
+/// can be played in, starting from any of the clocks the player may arrive
+/// with. Entities in `orb_registry` count as registered.
+pub fn validate_level(
+    level: &ldtk::Level,
+    orb_registry: &OrbRegistry,
+    arrivals: &[TimeState],
+    max_states: usize,
+) -> Validation {
+    let Some(layers) = level.layer_instances.as_ref() else {
+        return Validation {
+            problems: vec![LevelError::MissingLayers(level.identifier.clone()).into()],
+            finishes: Vec::new(),
+        };
+    };
+
+    let walls = layers.iter().find(|layer| {
+        layer.layer_instance_type == Type::IntGrid && layer.identifier == WALLS_LAYER
+    });
+    let in_wall = |grid: IVec2| {
+        walls.is_some_and(|walls| {
+            grid.x >= 0
+                && grid.x < walls.c_wid
+                && walls
+                    .int_grid_csv
+                    .get((grid.y * walls.c_wid + grid.x) as usize)
+                    == Some(&WALL_INT_CELL)
+        })
+    };
+
+    let mut problems = Vec::new();
+    let mut players = 0;
+    let mut goals = 0;
+
+    let entities = layers
+        .iter()
+        .filter(|layer| layer.layer_instance_type == Type::Entities)
+        .flat_map(|layer| &layer.entity_instances);
+
+    for entity in entities {
+        match entity.identifier.as_str() {
+            identifiers::PLAYER => players += 1,
+            identifiers::GOAL => goals += 1,
+            _ => {}
+        }
+
+        if !identifiers::REGISTERED.contains(&entity.identifier.as_str())
+            && !orb_registry.contains(&entity.identifier)
+        {
+            problems.push(Problem::UnregisteredEntity {
+                identifier: entity.identifier.clone(),
+                grid: entity.grid,
+            });
+        }
+
+        if in_wall(entity.grid) {
+            problems.push(Problem::EntityInWall {
+                identifier: entity.identifier.clone(),
+                grid: entity.grid,
+            });
+        }
+    }
+
+    match players {
+        0 => problems.push(Problem::MissingPlayer),
+        1 => {}
+        count => problems.push(Problem::DuplicatePlayer(count)),
+    }
+
+    if goals == 0 {
+        problems.push(Problem::MissingGoal);
+    }
+
+    // Only look for a route once there is exactly one player and a goal to
+    // route between.
+    let mut finishes = Vec::new();
+    if players == 1 && goals > 0 {
+        match Level::from_ldtk(level, orb_registry) {
+            Ok(level) => {
+                for start in level.starts(arrivals) {
+                    for mode in level.movement_modes() {
+                        match solve(&level, start, mode, max_states) {
+                            SolverResult::Solved(solution) => {
+                                if !finishes.contains(&solution.time_state) {
+                                    finishes.push(solution.time_state);
+                                }
+                            }
+                            SolverResult::Unsolvable => problems.push(Problem::GoalUnreachable {
+                                mode,
+                                start: start.time,
+                            }),
+                            SolverResult::GaveUp => problems.push(Problem::SolverGaveUp {
+                                mode,
+                                start: start.time,
+                                max_states,
+                            }),
+                        }
+                    }
+                }
+            }
+            Err(error) => problems.push(error.into()),
+        }
+    }
+
+    Validation { problems, finishes }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sim::level::tests::{add_entity, entity, ldtk_level};
+
+    fn validate(level: &ldtk::Level) -> Validation {
+        validate_level(
+            level,
+            &OrbRegistry::default(),
+            &[TimeState::default()],
+            1000,
+        )
+    }
+
+    #[test]
+    fn a_good_level_has_no_problems() {
+        let validation = validate(&ldtk_level(&["P.G"]));
+
+        assert!(validation.problems.is_empty(), "{:?}", validation.problems);
+        // Free movement runs the clock for every pixel up to the edge of the
+        // goal's tile, grid movement once per tile.
+        assert_eq!(
+            validation
+                .finishes
+                .iter()
+                .map(|finish| finish.time)
+                .collect::<Vec<_>>(),
+            vec![3 * crate::consts::GRID_SIZE / 2, 2]
+        );
+    }
+
+    #[test]
+    fn needs_one_player_and_a_goal() {
+        let problems = validate(&ldtk_level(&["..."])).problems;
+        assert!(matches!(
+            problems[..],
+            [Problem::MissingPlayer, Problem::MissingGoal]
+        ));
+
+        let problems = validate(&ldtk_level(&["PPG"])).problems;
+        assert!(matches!(problems[..], [Problem::DuplicatePlayer(2)]));
+    }
+
+    #[test]
+    fn finds_misplaced_and_unknown_entities() {
+        let mut level = ldtk_level(&["P#G"]);
+        add_entity(&mut level, entity(identifiers::HOSTILE, 1, 0));
+        add_entity(&mut level, entity("Mystery", 0, 0));
+
+        let problems = validate(&level).problems;
+        assert!(problems.iter().any(|problem| matches!(
+            problem,
+            Problem::EntityInWall { identifier, .. } if identifier == identifiers::HOSTILE
+        )));
+        assert!(problems.iter().any(|problem| matches!(
+            problem,
+            Problem::UnregisteredEntity { identifier, .. } if identifier == "Mystery"
+        )));
+    }
+
+    #[test]
+    fn reports_unreachable_goals_for_every_mode() {
+        let problems = validate(&ldtk_level(&["P#G"])).problems;
+
+        assert!(matches!(
+            problems[..],
+            [
+                Problem::GoalUnreachable {
+                    mode: MovementMode::Free,
+                    start: 0
+                },
+                Problem::GoalUnreachable {
+                    mode: MovementMode::Grid,
+                    start: 0
+                }
+            ]
+        ));
+    }
+
+    #[test]
+    fn solves_from_every_clock_the_player_can_arrive_with() {
+        let mut level = ldtk_level(&["P.G"]);
+        level.field_instances.push(ldtk::FieldInstance {
+            identifier: "Time_on_enter".to_string(),
+            tile: None,
+            field_instance_type: String::new(),
+            value: ldtk::FieldValue::Enum(Some("Keep".to_string())),
+            def_uid: 0,
+            real_editor_values: Vec::new(),
+        });
+        let arriving = |time| TimeState {
+            time,
+            ..TimeState::default()
+        };
+
+        let validation = validate_level(
+            &level,
+            &OrbRegistry::default(),
+            &[arriving(0), arriving(100), arriving(0)],
+            1000,
+        );
+
+        assert!(validation.problems.is_empty(), "{:?}", validation.problems);
+        assert_eq!(validation.finishes.len(), 4);
+        assert!(validation.finishes.contains(&TimeState {
+            start_time: 100,
+            ..arriving(102)
+        }));
+    }
+
+    #[test]
+    fn reports_when_the_solver_gives_up() {
+        let level = ldtk_level(&["P.........", "..........", ".........G"]);
+        let validation =
+            validate_level(&level, &OrbRegistry::default(), &[TimeState::default()], 3);
+
+        assert!(validation
+            .problems
+            .iter()
+            .all(|problem| matches!(problem, Problem::SolverGaveUp { max_states: 3, .. })));
+        assert!(validation.finishes.is_empty());
+    }
+}
@@ -0,0 +1,568 @@
+use bevy::prelude::*;
+use bevy_ecs_ldtk::{
+    utils::{grid_coords_to_translation, translation_to_grid_coords},
+    GridCoords,
+};
+
+use serde::{Deserialize, Serialize};
+
+use super::{DeathCause, Level, LevelState, Snapshot, TimeAxis, TimeState};
+use crate::consts;
+
+#[derive(Debug, PartialEq, Eq, Copy, Clone, Hash)]
+pub enum Direction {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+impl Direction {
+    pub const ALL: [Direction; 4] = [
+        Direction::Up,
+        Direction::Down,
+        Direction::Left,
+        Direction::Right,
+    ];
+
+    /// One grid cell in this direction.
+    pub fn offset(self) -> IVec2 {
+        match self {
+            Direction::Up => IVec2::Y,
+            Direction::Down => IVec2::NEG_Y,
+            Direction::Left => IVec2::NEG_X,
+            Direction::Right => IVec2::X,
+        }
+    }
+
+    /// The time axis a move in this direction travels along.
+    pub fn axis(self) -> TimeAxis {
+        match self {
+            Direction::Up | Direction::Down => TimeAxis::Vertical,
+            Direction::Left | Direction::Right => TimeAxis::Horizontal,
+        }
+    }
+
+    /// Whether a move in this direction runs the clock forwards or backwards.
+    pub fn sense(self) -> i32 {
+        match self {
+            Direction::Up | Direction::Right => 1,
+            Direction::Down | Direction::Left => -1,
+        }
+    }
+}
+
+/// A single move of the player. Either kind of step moves the clock by one
+/// `time_step_delta` if it runs along the current time axis.
+#[derive(Debug, PartialEq, Eq, Copy, Clone, Hash)]
+pub enum Step {
+    /// Free movement, covering `MOVEMENT_SPEED` pixels in one frame, or less
+    /// through a sapling.
+    Free(Direction),
+    /// Grid movement, from one tile to the middle of the next. Moving into a
+    /// sapling runs the clock twice, as it takes as long as crossing one in
+    /// free movement.
+    Tile(Direction),
+}
+
+impl Step {
+    pub fn direction(self) -> Direction {
+        match self {
+            Step::Free(direction) | Step::Tile(direction) => direction,
+        }
+    }
+}
+
+/// How the player moves through a level.
+#[derive(Default, Debug, PartialEq, Eq, Copy, Clone, Hash, Serialize, Deserialize)]
+pub enum MovementMode {
+    /// The player glides a few pixels every frame a key is held.
+    #[default]
+    Free,
+    /// Each key press moves the player exactly one tile.
+    Grid,
+}
+
+impl MovementMode {
+    pub const ALL: [MovementMode; 2] = [MovementMode::Free, MovementMode::Grid];
+
+    /// Maps the value of a level's `Movement` field to a mode.
+    pub fn from_identifier(identifier: &str) -> Option<Self> {
+        match identifier {
+            "Free" => Some(MovementMode::Free),
+            "Grid" => Some(MovementMode::Grid),
+            _ => None,
+        }
+    }
+}
+
+impl Level {
+    /// The movement modes the level can be played in: the one it sets, or
+    /// either if it leaves the choice to the player.
+    pub fn movement_modes(&self) -> Vec<MovementMode> {
+        match self.movement_mode {
+            Some(mode) => vec![mode],
+            None => MovementMode::ALL.to_vec(),
+        }
+    }
+}
+
+/// The parts of the player the rules care about.
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub struct Player {
+    pub translation: Vec2,
+    pub grid_coords: GridCoords,
+    /// Whether the player is standing on an orb. Orbs only fire when the
+    /// player walks into them, not while they stay inside.
+    pub in_orb: bool,
+}
+
+impl Player {
+    /// A player standing in the middle of `grid_coords`.
+    pub fn at(grid_coords: GridCoords) -> Self {
+        Self {
+            translation: grid_coords_to_translation(grid_coords, IVec2::splat(consts::GRID_SIZE)),
+            grid_coords,
+            in_orb: false,
+        }
+    }
+}
+
+/// What happened during a step.
+#[derive(Debug, Default, PartialEq, Eq, Copy, Clone)]
+pub struct StepOutcome {
+    /// False if a wall or a closed gate blocked the move.
+    pub moved: bool,
+    /// True if the move walked the player into an orb that still had charge.
+    pub entered_orb: bool,
+    /// True if the move went through a sapling, which slows the player down.
+    pub slowed: bool,
+    /// True if the player ends up on a goal that accepts them.
+    pub reached_goal: bool,
+    /// Set if the move killed the player.
+    pub died: Option<DeathCause>,
+}
+
+impl Level {
+    /// Moves `player` one step, updating `time_state` for the move and for any
+    /// orb the player walks into, and `level_state` for the orb charges used
+    /// and recharged, the footprints left behind and any block pushed.
+    pub fn step(
+        &self,
+        player: &mut Player,
+        time_state: &mut TimeState,
+        level_state: &mut LevelState,
+        step: Step,
+    ) -> StepOutcome {
+        let direction = step.direction();
+        let slowed = match step {
+            Step::Free(_) => self.plant_slows(&player.grid_coords, time_state),
+            Step::Tile(_) => self.plant_slows(
+                &(player.grid_coords + GridCoords::from(direction.offset())),
+                time_state,
+            ),
+        };
+        let (translation, grid_coords) = match step {
+            Step::Free(_) => {
+                let speed = if slowed {
+                    consts::MOVEMENT_SPEED * consts::SAPLING_SPEED_FACTOR
+                } else {
+                    consts::MOVEMENT_SPEED
+                };
+                let translation = player.translation + direction.offset().as_vec2() * speed as f32;
+                (
+                    translation,
+                    translation_to_grid_coords(translation, IVec2::splat(consts::GRID_SIZE)),
+                )
+            }
+            Step::Tile(_) => {
+                let grid_coords = player.grid_coords + GridCoords::from(direction.offset());
+                (
+                    grid_coords_to_translation(grid_coords, IVec2::splat(consts::GRID_SIZE)),
+                    grid_coords,
+                )
+            }
+        };
+
+        // Only check the tile being walked into, so a gate closing on the
+        // player doesn't trap them. A block in the way is pushed ahead.
+        if grid_coords != player.grid_coords
+            && (self.impassable(&grid_coords, time_state, level_state, &player.grid_coords)
+                || !self.push_block(
+                    &grid_coords,
+                    direction,
+                    level_state,
+                    time_state,
+                    &player.grid_coords,
+                ))
+        {
+            return StepOutcome::default();
+        }
+
+        player.translation = translation;
+        let time_before = *time_state;
+        time_state.advance(direction.axis(), direction.sense());
+        if slowed && matches!(step, Step::Tile(_)) {
+            time_state.advance(direction.axis(), direction.sense());
+        }
+        self.recharge_orbs(level_state, &time_before, time_state);
+
+        let mut outcome = StepOutcome {
+            moved: true,
+            slowed,
+            ..default()
+        };
+
+        let from = player.grid_coords;
+        if grid_coords != player.grid_coords {
+            player.grid_coords = grid_coords;
+
+            let mut on_orb = false;
+            for (orb, charges) in self.orbs.iter().zip(&mut level_state.orb_charges) {
+                if orb.grid_coords != grid_coords {
+                    continue;
+                }
+                on_orb = true;
+                if !player.in_orb && *charges != Some(0) {
+                    orb.effect.apply(time_state);
+                    if let Some(charges) = charges {
+                        *charges -= 1;
+                    }
+                    outcome.entered_orb = true;
+                }
+            }
+            player.in_orb = on_orb;
+        }
+
+        // After the orbs, so ice sees every season the clock went through.
+        self.weather_blocks(level_state, &time_before, time_state);
+
+        if time_state.time != time_before.time {
+            self.leave_footprint(level_state, time_before.time, from);
+        }
+
+        outcome.died = self.death_cause(&player.grid_coords, time_state);
+        outcome.reached_goal =
+            outcome.died.is_none() && self.goal_reached(&player.grid_coords, time_state);
+        outcome
+    }
+}
+
+/// Where a move would leave the clock, and what else it would do.
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub struct Preview {
+    pub time_state: TimeState,
+    pub outcome: StepOutcome,
+}
+
+impl Level {
+    /// Works out what moving to the next tile in `direction` would do by
+    /// making the move on copies of the state, so it always agrees with the
+    /// real thing. Free movement takes as many small steps as it needs to
+    /// cross into the tile, even through a sapling. Gives `None` if the way
+    /// is blocked.
+    pub fn preview(
+        &self,
+        player: &Player,
+        time_state: &TimeState,
+        level_state: &LevelState,
+        mode: MovementMode,
+        direction: Direction,
+    ) -> Option<Preview> {
+        let mut player = *player;
+        let mut time_state = *time_state;
+        let mut level_state = level_state.clone();
+        let start = player.grid_coords;
+
+        let (step, max_steps) = match mode {
+            MovementMode::Grid => (Step::Tile(direction), 1),
+            MovementMode::Free => (
+                Step::Free(direction),
+                (consts::GRID_SIZE as f64 / (consts::MOVEMENT_SPEED * consts::SAPLING_SPEED_FACTOR))
+                    .ceil() as usize,
+            ),
+        };
+
+        for _ in 0..max_steps {
+            let outcome = self.step(&mut player, &mut time_state, &mut level_state, step);
+            if !outcome.moved {
+                return None;
+            }
+            if player.grid_coords != start || outcome.died.is_some() {
+                return Some(Preview {
+                    time_state,
+                    outcome,
+                });
+            }
+        }
+        None
+    }
+
+    /// Gives used orbs their charges back if the clock has just come into
+    /// their recharge time.
+    fn recharge_orbs(&self, level_state: &mut LevelState, before: &TimeState, after: &TimeState) {
+        for (orb, charges) in self.orbs.iter().zip(&mut level_state.orb_charges) {
+            if let Some(recharge) = orb.recharge {
+                if !recharge.is_due(before) && recharge.is_due(after) {
+                    *charges = orb.charges;
+                }
+            }
+        }
+    }
+}
+
+/// A level being played, with everything needed to step it forward.
+#[derive(Debug, Clone)]
+pub struct World {
+    pub level: Level,
+    pub player: Player,
+    pub time_state: TimeState,
+    pub level_state: LevelState,
+}
+
+impl World {
+    pub fn new(level: Level) -> Self {
+        Self {
+            player: Player::at(level.player_start),
+            level_state: level.initial_state(),
+            level,
+            time_state: TimeState::default(),
+        }
+    }
+
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            player: self.player,
+            time_state: self.time_state,
+            level_state: self.level_state.clone(),
+        }
+    }
+
+    pub fn restore(&mut self, snapshot: Snapshot) {
+        self.player = snapshot.player;
+        self.time_state = snapshot.time_state;
+        self.level_state = snapshot.level_state;
+    }
+
+    pub fn step(&mut self, step: Step) -> StepOutcome {
+        self.level.step(
+            &mut self.player,
+            &mut self.time_state,
+            &mut self.level_state,
+            step,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use bevy::utils::HashSet;
+
+    use super::*;
+    use crate::sim::{DirectionSwitch, Goal, Orb, Recharge, Walls};
+
+    fn corridor(length: i32) -> Level {
+        Level::empty(length, 1)
+    }
+
+    fn orb(x: i32, charges: Option<u32>, recharge: Option<Recharge>) -> Orb {
+        Orb {
+            effect: Arc::new(DirectionSwitch),
+            grid_coords: GridCoords::new(x, 0),
+            charges,
+            recharge,
+        }
+    }
+
+    #[test]
+    fn tile_steps_run_the_clock_once_per_tile() {
+        let mut world = World::new(Level {
+            player_start: GridCoords::new(1, 1),
+            ..Level::empty(3, 3)
+        });
+
+        assert!(world.step(Step::Tile(Direction::Right)).moved);
+        assert_eq!(world.player.grid_coords, GridCoords::new(2, 1));
+        assert_eq!(world.time_state.time, 1);
+
+        world.step(Step::Tile(Direction::Left));
+        world.step(Step::Tile(Direction::Left));
+        assert_eq!(world.time_state.time, -1);
+
+        // Off the time axis, the clock stands still.
+        world.step(Step::Tile(Direction::Up));
+        assert_eq!(world.player.grid_coords, GridCoords::new(0, 2));
+        assert_eq!(world.time_state.time, -1);
+    }
+
+    #[test]
+    fn free_steps_run_the_clock_every_frame() {
+        let mut world = World::new(corridor(3));
+
+        // From the middle of a tile, half a tile to the edge.
+        for _ in 0..consts::GRID_SIZE / 2 - 1 {
+            assert!(world.step(Step::Free(Direction::Right)).moved);
+        }
+        assert_eq!(world.player.grid_coords, GridCoords::new(0, 0));
+
+        world.step(Step::Free(Direction::Right));
+        assert_eq!(world.player.grid_coords, GridCoords::new(1, 0));
+        assert_eq!(world.time_state.time, consts::GRID_SIZE / 2);
+    }
+
+    #[test]
+    fn walls_and_the_level_edge_block_moves() {
+        let mut world = World::new(Level {
+            walls: Walls::new(HashSet::from_iter([GridCoords::new(1, 0)]), 2, 1),
+            ..corridor(2)
+        });
+
+        for step in [Step::Tile(Direction::Right), Step::Tile(Direction::Left)] {
+            assert_eq!(world.step(step), StepOutcome::default());
+        }
+        assert_eq!(world.player, Player::at(GridCoords::new(0, 0)));
+        assert_eq!(world.time_state, TimeState::default());
+
+        // Free movement can still walk up to the wall.
+        for _ in 0..consts::GRID_SIZE {
+            world.step(Step::Free(Direction::Right));
+        }
+        assert_eq!(world.player.grid_coords, GridCoords::new(0, 0));
+        assert_eq!(world.time_state.time, consts::GRID_SIZE / 2 - 1);
+    }
+
+    #[test]
+    fn water_only_blocks_until_winter() {
+        let mut level = corridor(2);
+        level.water.insert(GridCoords::new(1, 0));
+        let mut world = World::new(level);
+
+        assert!(!world.step(Step::Tile(Direction::Right)).moved);
+
+        world.time_state.time = 3 * consts::DAYS_PER_SEASION * 24 * 60;
+        assert!(world.step(Step::Tile(Direction::Right)).moved);
+    }
+
+    #[test]
+    fn orbs_fire_once_on_the_way_in() {
+        let mut world = World::new(Level {
+            orbs: vec![orb(1, None, None)],
+            ..corridor(3)
+        });
+
+        let mut fired = 0;
+        for _ in 0..consts::GRID_SIZE {
+            if world.step(Step::Free(Direction::Right)).entered_orb {
+                fired += 1;
+            }
+        }
+
+        assert_eq!(fired, 1);
+        assert!(world.player.in_orb);
+        assert_eq!(world.time_state.time_step_delta, -1);
+    }
+
+    #[test]
+    fn orbs_run_out_and_recharge() {
+        let recharge = Recharge {
+            hour: Some(1),
+            seasion: None,
+        };
+        let mut world = World::new(Level {
+            orbs: vec![orb(1, Some(1), Some(recharge))],
+            ..corridor(4)
+        });
+
+        assert!(world.step(Step::Tile(Direction::Right)).entered_orb);
+        assert!(world
+            .level
+            .orb_spent(&world.level_state, &GridCoords::new(1, 0)));
+
+        world.step(Step::Tile(Direction::Right));
+        assert!(!world.step(Step::Tile(Direction::Left)).entered_orb);
+
+        // Moving left runs the reversed clock forwards, into hour 1.
+        world.step(Step::Tile(Direction::Right));
+        world.step(Step::Tile(Direction::Right));
+        world.time_state.time = 59;
+        world.step(Step::Tile(Direction::Left));
+        assert_eq!(world.level_state.orb_charges, vec![Some(1)]);
+        assert!(world.step(Step::Tile(Direction::Left)).entered_orb);
+    }
+
+    #[test]
+    fn preview_agrees_with_the_move() {
+        let level = Level {
+            orbs: vec![orb(1, None, None)],
+            ..corridor(3)
+        };
+
+        for (mode, step) in [
+            (MovementMode::Grid, Step::Tile(Direction::Right)),
+            (MovementMode::Free, Step::Free(Direction::Right)),
+        ] {
+            let mut world = World::new(level.clone());
+            let preview = level
+                .preview(
+                    &world.player,
+                    &world.time_state,
+                    &world.level_state,
+                    mode,
+                    Direction::Right,
+                )
+                .unwrap();
+
+            let mut outcome = world.step(step);
+            while world.player.grid_coords == level.player_start {
+                outcome = world.step(step);
+            }
+            assert_eq!(preview.time_state, world.time_state);
+            assert_eq!(preview.outcome, outcome);
+            assert!(preview.outcome.entered_orb);
+        }
+    }
+
+    #[test]
+    fn no_preview_into_a_wall() {
+        let world = World::new(corridor(2));
+
+        for mode in MovementMode::ALL {
+            assert_eq!(
+                world.level.preview(
+                    &world.player,
+                    &world.time_state,
+                    &world.level_state,
+                    mode,
+                    Direction::Left,
+                ),
+                None
+            );
+        }
+    }
+
+    #[test]
+    fn levels_without_a_mode_can_be_played_in_either() {
+        let mut level = Level::empty(1, 1);
+        assert_eq!(level.movement_modes(), MovementMode::ALL.to_vec());
+
+        level.movement_mode = Some(MovementMode::Grid);
+        assert_eq!(level.movement_modes(), vec![MovementMode::Grid]);
+    }
+
+    #[test]
+    fn reaching_the_goal() {
+        let mut world = World::new(Level {
+            goals: vec![Goal {
+                grid_coords: GridCoords::new(1, 0),
+                requirements: Vec::new(),
+            }],
+            ..corridor(2)
+        });
+
+        let reached =
+            (0..consts::GRID_SIZE).any(|_| world.step(Step::Free(Direction::Right)).reached_goal);
+        assert!(reached);
+    }
+}
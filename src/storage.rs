@@ -0,0 +1,80 @@
+//! Small text files the game keeps between runs, such as the save and
+//! recorded replays. Native builds keep them in the platform data directory,
+//! the wasm build keeps them in the browser's local storage.
+
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum StorageError {
+    #[cfg(not(target_arch = "wasm32"))]
+    #[error("no data directory to keep {0} in")]
+    NoDataDir(String),
+    #[cfg(not(target_arch = "wasm32"))]
+    #[error("couldn't access {0}: {1}")]
+    Io(std::path::PathBuf, std::io::Error),
+    #[cfg(target_arch = "wasm32")]
+    #[error("browser local storage is unavailable")]
+    NoLocalStorage,
+}
+
+pub use platform::{read, write};
+
+#[cfg(not(target_arch = "wasm32"))]
+mod platform {
+    use std::{fs, io, path::PathBuf};
+
+    use super::StorageError;
+
+    fn path(name: &str) -> Result<PathBuf, StorageError> {
+        Ok(dirs::data_dir()
+            .ok_or_else(|| StorageError::NoDataDir(name.to_string()))?
+            .join("cycle_game")
+            .join(name))
+    }
+
+    /// Reads the file called `name`, or `None` if there isn't one yet.
+    pub fn read(name: &str) -> Result<Option<String>, StorageError> {
+        let path = path(name)?;
+        match fs::read_to_string(&path) {
+            Ok(contents) => Ok(Some(contents)),
+            Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(error) => Err(StorageError::Io(path, error)),
+        }
+    }
+
+    pub fn write(name: &str, contents: &str) -> Result<(), StorageError> {
+        let path = path(name)?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).map_err(|error| StorageError::Io(dir.to_path_buf(), error))?;
+        }
+        fs::write(&path, contents).map_err(|error| StorageError::Io(path, error))
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+mod platform {
+    use super::StorageError;
+
+    fn local_storage() -> Result<web_sys::Storage, StorageError> {
+        web_sys::window()
+            .and_then(|window| window.local_storage().ok().flatten())
+            .ok_or(StorageError::NoLocalStorage)
+    }
+
+    fn key(name: &str) -> String {
+        format!("cycle_game.{name}")
+    }
+
+    /// Reads the item called `name`, or `None` if there isn't one yet.
+    pub fn read(name: &str) -> Result<Option<String>, StorageError> {
+        local_storage()?
+            .get_item(&key(name))
+            .map_err(|_| StorageError::NoLocalStorage)
+    }
+
+    pub fn write(name: &str, contents: &str) -> Result<(), StorageError> {
+        local_storage()?
+            .set_item(&key(name), contents)
+            .map_err(|_| StorageError::NoLocalStorage)
+    }
+}
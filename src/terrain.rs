@@ -0,0 +1,131 @@
+use bevy::{prelude::*, utils::HashMap};
+use bevy_ecs_ldtk::prelude::*;
+use bevy_ecs_tilemap::{map::TilemapId, tiles::TileTextureIndex};
+use cycle_game::sim::Seasion;
+
+use crate::game_state::{GameState, TimeState};
+
+/// Swaps tiles for seasonal variants as the clock moves through the year.
+///
+/// Variants are authored in LDtk as custom data on the tileset's tiles, one
+/// `Season=tile_id` line per season, e.g. `Winter=612`. Seasons without a line
+/// show the tile as placed.
+pub struct TerrainPlugin;
+
+impl Plugin for TerrainPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (tag_seasonal_tiles, update_seasonal_tiles)
+                .chain()
+                .run_if(in_state(GameState::Playing)),
+        );
+    }
+}
+
+/// A tile with a variant for at least one season.
+#[derive(Component, Debug)]
+pub struct SeasonalTile {
+    base: u32,
+    variants: [Option<u32>; 4],
+}
+
+impl SeasonalTile {
+    fn index_for(&self, seasion: Seasion) -> u32 {
+        self.variants[seasion as usize].unwrap_or(self.base)
+    }
+}
+
+fn parse_variants(data: &str) -> [Option<u32>; 4] {
+    let mut variants = [None; 4];
+    for line in data.lines() {
+        let Some((seasion, tile_id)) = line.split_once('=') else {
+            continue;
+        };
+        match (
+            Seasion::from_identifier(seasion.trim()),
+            tile_id.trim().parse(),
+        ) {
+            (Some(seasion), Ok(tile_id)) => variants[seasion as usize] = Some(tile_id),
+            _ => warn!("ignoring seasonal tile data {line:?}"),
+        }
+    }
+    variants
+}
+
+fn tag_seasonal_tiles(
+    mut commands: Commands,
+    tiles: Query<(Entity, &TileTextureIndex, &TilemapId), Added<TileTextureIndex>>,
+    layers: Query<&LayerMetadata>,
+    ldtk_project_entities: Query<&Handle<LdtkProject>>,
+    ldtk_project_assets: Res<Assets<LdtkProject>>,
+) {
+    if tiles.is_empty() {
+        return;
+    }
+
+    let Some(ldtk_project) = ldtk_project_entities
+        .get_single()
+        .ok()
+        .and_then(|handle| ldtk_project_assets.get(handle))
+    else {
+        return;
+    };
+
+    let variants: HashMap<(i32, u32), [Option<u32>; 4]> = ldtk_project
+        .json_data()
+        .defs
+        .tilesets
+        .iter()
+        .flat_map(|tileset| {
+            tileset.custom_data.iter().map(|custom| {
+                (
+                    (tileset.uid, custom.tile_id as u32),
+                    parse_variants(&custom.data),
+                )
+            })
+        })
+        .filter(|(_, variants)| variants.iter().any(Option::is_some))
+        .collect();
+
+    if variants.is_empty() {
+        return;
+    }
+
+    for (entity, texture_index, tilemap_id) in &tiles {
+        let Some(tileset_uid) = layers
+            .get(tilemap_id.0)
+            .ok()
+            .and_then(|layer| layer.tileset_def_uid)
+        else {
+            continue;
+        };
+
+        if let Some(variants) = variants.get(&(tileset_uid, texture_index.0)) {
+            commands.entity(entity).insert(SeasonalTile {
+                base: texture_index.0,
+                variants: *variants,
+            });
+        }
+    }
+}
+
+fn update_seasonal_tiles(
+    time_state: Res<TimeState>,
+    mut tiles: Query<(&SeasonalTile, &mut TileTextureIndex)>,
+    new_tiles: Query<(), Added<SeasonalTile>>,
+    mut shown_seasion: Local<Option<Seasion>>,
+) {
+    let seasion = time_state.current_seasion();
+    if *shown_seasion == Some(seasion) && new_tiles.is_empty() {
+        return;
+    }
+    *shown_seasion = Some(seasion);
+
+    for (tile, mut texture_index) in &mut tiles {
+        let index = tile.index_for(seasion);
+        if texture_index.0 != index {
+            texture_index.0 = index;
+        }
+    }
+}
@@ -0,0 +1,258 @@
+use bevy::prelude::*;
+use bevy_ecs_ldtk::{prelude::*, utils::grid_coords_to_translation};
+use cycle_game::{
+    bindings::Action,
+    consts,
+    sim::{self, Direction, LevelState, MovementMode, Preview},
+};
+
+use crate::{
+    actions::ActionState,
+    game_state::{GameState, PlayState, TimeState},
+    level::CurrentLevel,
+    player::{MovementSettings, Player, PlayerInOrb},
+};
+
+const BLOCKED_COLOR: Color = Color::srgb(0.6, 0.6, 0.6);
+const DEADLY_COLOR: Color = Color::srgb(1.0, 0.3, 0.3);
+const GOAL_COLOR: Color = Color::srgb(0.4, 1.0, 0.4);
+const ORB_COLOR: Color = Color::srgb(1.0, 0.85, 0.2);
+
+pub struct TimePreviewPlugin;
+
+impl Plugin for TimePreviewPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<TimePreview>()
+            .add_systems(OnEnter(GameState::Playing), spawn_preview_overlay)
+            .add_systems(OnExit(GameState::Playing), despawn_preview_overlay)
+            .add_systems(
+                Update,
+                (toggle_time_preview, update_preview_labels)
+                    .chain()
+                    .run_if(in_state(PlayState::Running)),
+            )
+            .add_systems(OnEnter(PlayState::Paused), hide_preview_overlay);
+    }
+}
+
+/// Whether the tiles around the player are labelled with the time a move
+/// there would arrive at.
+#[derive(Resource)]
+pub struct TimePreview {
+    pub enabled: bool,
+}
+
+impl Default for TimePreview {
+    fn default() -> Self {
+        Self { enabled: true }
+    }
+}
+
+#[derive(Component)]
+pub struct TimePreviewOverlay;
+
+/// The label over the tile next to the player in this direction.
+#[derive(Component)]
+pub struct PreviewLabel(Direction);
+
+fn toggle_time_preview(mut time_preview: ResMut<TimePreview>, actions: Res<ActionState>) {
+    if actions.just_pressed(Action::TogglePreview) {
+        time_preview.enabled = !time_preview.enabled;
+    }
+}
+
+fn spawn_preview_overlay(mut commands: Commands) {
+    let container = NodeBundle {
+        style: Style {
+            position_type: PositionType::Absolute,
+            width: Val::Percent(100.0),
+            height: Val::Percent(100.0),
+            ..default()
+        },
+        visibility: Visibility::Hidden,
+        ..default()
+    };
+
+    commands
+        .spawn((container, TimePreviewOverlay))
+        .with_children(|overlay| {
+            for direction in Direction::ALL {
+                overlay.spawn((
+                    TextBundle::from_section(
+                        "",
+                        TextStyle {
+                            font_size: 14.0,
+                            color: Color::WHITE,
+                            ..default()
+                        },
+                    )
+                    .with_text_justify(JustifyText::Center)
+                    .with_style(Style {
+                        position_type: PositionType::Absolute,
+                        padding: UiRect::all(Val::Px(2.0)),
+                        ..default()
+                    })
+                    .with_background_color(Color::srgba(0.0, 0.0, 0.0, 0.6)),
+                    PreviewLabel(direction),
+                ));
+            }
+        });
+}
+
+fn despawn_preview_overlay(mut commands: Commands, query: Query<Entity, With<TimePreviewOverlay>>) {
+    let Ok(overlay) = query.get_single() else {
+        return;
+    };
+    commands.entity(overlay).despawn_recursive();
+}
+
+fn hide_preview_overlay(mut overlays: Query<&mut Visibility, With<TimePreviewOverlay>>) {
+    for mut visibility in &mut overlays {
+        *visibility = Visibility::Hidden;
+    }
+}
+
+/// What the previews were last worked out from.
+#[derive(PartialEq)]
+struct PreviewKey {
+    level_iid: String,
+    grid_coords: GridCoords,
+    in_orb: bool,
+    time_state: TimeState,
+    level_state: LevelState,
+    mode: MovementMode,
+}
+
+/// The last previews worked out, in the order of [`Direction::ALL`]. Free
+/// movement previews take up to a tile's worth of steps each, so they are
+/// only worked out again once the player changes tile or the clock or level
+/// changes.
+#[derive(Default)]
+struct PreviewCache {
+    key: Option<PreviewKey>,
+    previews: [Option<Preview>; 4],
+}
+
+impl PreviewCache {
+    fn update(
+        &mut self,
+        level: &sim::Level,
+        player: &sim::Player,
+        time_state: &TimeState,
+        level_state: &LevelState,
+        mode: MovementMode,
+    ) {
+        let key = PreviewKey {
+            level_iid: level.iid.clone(),
+            grid_coords: player.grid_coords,
+            in_orb: player.in_orb,
+            time_state: *time_state,
+            level_state: level_state.clone(),
+            mode,
+        };
+        if self.key.as_ref() == Some(&key) {
+            return;
+        }
+
+        self.previews = Direction::ALL
+            .map(|direction| level.preview(player, time_state, level_state, mode, direction));
+        self.key = Some(key);
+    }
+
+    fn get(&self, direction: Direction) -> Option<Preview> {
+        let index = Direction::ALL
+            .iter()
+            .position(|other| *other == direction)
+            .expect("every direction is in Direction::ALL");
+        self.previews[index]
+    }
+}
+
+/// Runs each of the four moves through the rules, without keeping the
+/// results, and labels the tile it heads for with where the clock would be.
+fn update_preview_labels(
+    mut cache: Local<PreviewCache>,
+    time_preview: Res<TimePreview>,
+    current_level: Res<CurrentLevel>,
+    time_state: Res<TimeState>,
+    level_state: Res<LevelState>,
+    movement_settings: Res<MovementSettings>,
+    players: Query<(&Transform, &GridCoords, Has<PlayerInOrb>, &Parent), With<Player>>,
+    global_transforms: Query<&GlobalTransform>,
+    cameras: Query<(&Camera, &GlobalTransform)>,
+    mut overlays: Query<&mut Visibility, With<TimePreviewOverlay>>,
+    mut labels: Query<(&PreviewLabel, &mut Style, &mut Text, &Node)>,
+) {
+    let Ok(mut overlay_visibility) = overlays.get_single_mut() else {
+        return;
+    };
+    let (Ok((transform, grid_coords, in_orb, parent)), Ok((camera, camera_transform))) =
+        (players.get_single(), cameras.get_single())
+    else {
+        *overlay_visibility = Visibility::Hidden;
+        return;
+    };
+    let Ok(layer_transform) = global_transforms.get(parent.get()) else {
+        return;
+    };
+
+    overlay_visibility.set_if_neq(if time_preview.enabled {
+        Visibility::Inherited
+    } else {
+        Visibility::Hidden
+    });
+    if !time_preview.enabled {
+        return;
+    }
+
+    let player = sim::Player {
+        translation: transform.translation.xy(),
+        grid_coords: *grid_coords,
+        in_orb,
+    };
+    let mode = movement_settings.mode_for(&current_level);
+    cache.update(&current_level, &player, &time_state, &level_state, mode);
+
+    for (PreviewLabel(direction), mut style, mut text, node) in &mut labels {
+        let target = *grid_coords + GridCoords::from(direction.offset());
+        let tile_centre = layer_transform.transform_point(
+            grid_coords_to_translation(target, IVec2::splat(consts::GRID_SIZE)).extend(0.0),
+        );
+        let Some(position) = camera.world_to_viewport(camera_transform, tile_centre) else {
+            continue;
+        };
+        style.left = Val::Px(position.x - node.size().x / 2.0);
+        style.top = Val::Px(position.y - node.size().y / 2.0);
+
+        let preview = cache.get(*direction);
+        let (value, color) = match preview {
+            None => ("blocked".to_string(), BLOCKED_COLOR),
+            Some(preview) => {
+                let arrival = preview.time_state;
+                let color = if preview.outcome.died.is_some() {
+                    DEADLY_COLOR
+                } else if preview.outcome.reached_goal {
+                    GOAL_COLOR
+                } else if preview.outcome.entered_orb {
+                    ORB_COLOR
+                } else {
+                    Color::WHITE
+                };
+                (
+                    format!(
+                        "{}:00\n{}",
+                        arrival.current_hour(),
+                        arrival.current_seasion()
+                    ),
+                    color,
+                )
+            }
+        };
+
+        let section = &mut text.sections[0];
+        if section.value != value {
+            section.value = value;
+        }
+        section.style.color = color;
+    }
+}
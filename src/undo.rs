@@ -0,0 +1,85 @@
+use bevy::prelude::*;
+use bevy_ecs_ldtk::prelude::*;
+use cycle_game::{
+    bindings::Action,
+    sim::{self, Input, LevelState},
+};
+
+use crate::{
+    actions::ActionState,
+    game_state::{PlayState, TimeState},
+    level::CurrentLevel,
+    level_complete::LevelStats,
+    player::{Player, SimPlayer},
+    replay::{playback_inactive, Recording},
+};
+
+pub struct UndoPlugin;
+
+impl Plugin for UndoPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<MoveHistory>().add_systems(
+            Update,
+            (
+                clear_history_on_level_spawn,
+                undo_from_input.run_if(in_state(PlayState::Running).and_then(playback_inactive)),
+            ),
+        );
+    }
+}
+
+/// Snapshots taken before each move the player makes in the current level.
+#[derive(Default, Resource, Debug, Deref, DerefMut)]
+pub struct MoveHistory(sim::History);
+
+fn clear_history_on_level_spawn(
+    mut history: ResMut<MoveHistory>,
+    mut level_events: EventReader<LevelEvent>,
+) {
+    for level_event in level_events.read() {
+        if let LevelEvent::Spawned(_) = level_event {
+            history.clear();
+        }
+    }
+}
+
+/// Undo steps back a move, redo steps forward again.
+fn undo_from_input(
+    mut commands: Commands,
+    mut history: ResMut<MoveHistory>,
+    mut time_state: ResMut<TimeState>,
+    mut level_state: ResMut<LevelState>,
+    mut stats: ResMut<LevelStats>,
+    mut recording: ResMut<Recording>,
+    mut players: Query<SimPlayer, With<Player>>,
+    actions: Res<ActionState>,
+    current_level: Res<CurrentLevel>,
+) {
+    let Ok(mut sim_player) = players.get_single_mut() else {
+        return;
+    };
+
+    let (input, moves) = if actions.just_pressed(Action::Undo) {
+        (Input::Undo, stats.moves.saturating_sub(1))
+    } else if actions.just_pressed(Action::Redo) {
+        (Input::Redo, stats.moves + 1)
+    } else {
+        return;
+    };
+
+    let mut player = sim_player.to_sim();
+    if input
+        .apply(
+            &current_level,
+            &mut history,
+            &mut player,
+            &mut time_state,
+            &mut level_state,
+        )
+        .moved
+    {
+        stats.moves = moves;
+        recording.push(input);
+        sim_player.apply(&mut commands, &player);
+    }
+}
@@ -1,5 +1,7 @@
-use bevy::{prelude::*, utils::HashSet};
+use bevy::prelude::*;
 use bevy_ecs_ldtk::prelude::*;
+use cycle_game::sim::WALL_INT_CELL;
+
 #[derive(Default, Component)]
 struct Wall;
 
@@ -12,56 +14,6 @@ pub struct WallPlugin;
 
 impl Plugin for WallPlugin {
     fn build(&self, app: &mut App) {
-        app.init_resource::<LevelWalls>()
-            .register_ldtk_int_cell::<WallBundle>(1)
-            .add_systems(Update, cache_wall_locations);
-    }
-}
-
-/// The following caches wall locations when the level spwans to allow for
-/// a quick lookup
-#[derive(Default, Resource, Debug)]
-pub struct LevelWalls {
-    wall_locations: HashSet<GridCoords>,
-    level_width: i32,
-    level_height: i32,
-}
-
-impl LevelWalls {
-    pub fn in_wall(&self, grid_coords: &GridCoords) -> bool {
-        grid_coords.x < 0
-            || grid_coords.y < 0
-            || grid_coords.x >= self.level_width
-            || grid_coords.y >= self.level_height
-            || self.wall_locations.contains(grid_coords)
-    }
-}
-
-fn cache_wall_locations(
-    mut level_walls: ResMut<LevelWalls>,
-    mut level_events: EventReader<LevelEvent>,
-    walls: Query<&GridCoords, With<Wall>>,
-    ldtk_project_entities: Query<&Handle<LdtkProject>>,
-    ldtk_project_assets: Res<Assets<LdtkProject>>,
-) {
-    for level_event in level_events.read() {
-        if let LevelEvent::Spawned(level_iid) = level_event {
-            let ldtk_project = ldtk_project_assets
-                .get(ldtk_project_entities.single())
-                .expect("LdtkProject should be loaded when level is spawned");
-            let level = ldtk_project
-                .get_raw_level_by_iid(level_iid.get())
-                .expect("spawned level should exist in project");
-
-            let wall_locations = walls.iter().copied().collect();
-
-            let new_level_walls = LevelWalls {
-                wall_locations,
-                level_width: level.px_wid / crate::consts::GRID_SIZE,
-                level_height: level.px_hei / crate::consts::GRID_SIZE,
-            };
-
-            *level_walls = new_level_walls;
-        }
+        app.register_ldtk_int_cell::<WallBundle>(WALL_INT_CELL);
     }
 }
@@ -1,6 +1,12 @@
 use bevy::prelude::*;
+use bevy_ecs_ldtk::prelude::*;
+use cycle_game::bindings::{Action, Bindings};
 
-use crate::game_state::GameState;
+use crate::{
+    actions::{self, ActionState},
+    game_state::GameState,
+    progress::Progress,
+};
 
 pub struct WelcomeScreenPlugin;
 
@@ -10,7 +16,9 @@ impl Plugin for WelcomeScreenPlugin {
             .add_systems(OnExit(GameState::WelcomeScreen), despawn_welcome_screen)
             .add_systems(
                 Update,
-                menu_screen_key_press.run_if(in_state(GameState::WelcomeScreen)),
+                (menu_screen_key_press, pick_level, update_level_picker)
+                    .chain()
+                    .run_if(in_state(GameState::WelcomeScreen)),
             );
     }
 }
@@ -18,17 +26,59 @@ impl Plugin for WelcomeScreenPlugin {
 #[derive(Component)]
 pub struct WelcomeScreen;
 
+/// Shows which of the unlocked levels play starts from.
+#[derive(Component)]
+pub struct LevelPicker;
+
 pub fn menu_screen_key_press(
     mut next_state: ResMut<NextState<GameState>>,
 
-    keyboard_input: Res<ButtonInput<KeyCode>>,
+    actions: Res<ActionState>,
 ) {
-    if keyboard_input.just_pressed(KeyCode::Space) {
+    if actions.just_pressed(Action::Confirm) {
         next_state.set(GameState::Playing)
+    } else if actions.just_pressed(Action::Controls) {
+        next_state.set(GameState::Controls)
     }
 }
 
-pub fn spawn_welcome_screen(mut commands: Commands) {
+/// Moving left and right steps through the unlocked levels.
+pub fn pick_level(
+    mut level_selection: ResMut<LevelSelection>,
+    progress: Res<Progress>,
+    actions: Res<ActionState>,
+) {
+    let LevelSelection::Indices(indices) = level_selection.as_mut() else {
+        panic!("level selection should always be Indices in this game");
+    };
+
+    if actions.just_pressed(Action::MoveLeft) && indices.level > 0 {
+        indices.level -= 1;
+    } else if actions.just_pressed(Action::MoveRight)
+        && indices.level + 1 < progress.unlocked_levels
+    {
+        indices.level += 1;
+    }
+}
+
+pub fn update_level_picker(
+    level_selection: Res<LevelSelection>,
+    progress: Res<Progress>,
+    mut level_picker: Query<&mut Text, With<LevelPicker>>,
+) {
+    let LevelSelection::Indices(indices) = *level_selection else {
+        return;
+    };
+
+    let mut level_picker = level_picker.single_mut();
+    level_picker.sections[0].value = if progress.unlocked_levels > 1 {
+        format!("< level {} >", indices.level + 1)
+    } else {
+        String::new()
+    };
+}
+
+pub fn spawn_welcome_screen(mut commands: Commands, bindings: Res<Bindings>) {
     let container = NodeBundle {
         style: Style {
             width: Val::Percent(100.0),
@@ -58,7 +108,10 @@ pub fn spawn_welcome_screen(mut commands: Commands) {
     let prompt = commands
         .spawn(
             TextBundle::from_sections([TextSection::new(
-                "press space to start!",
+                format!(
+                    "press {} to start!",
+                    actions::describe(&bindings, Action::Confirm)
+                ),
                 TextStyle {
                     font_size: 75.0,
                     color: Color::WHITE,
@@ -69,9 +122,43 @@ pub fn spawn_welcome_screen(mut commands: Commands) {
         )
         .id();
 
+    let level_picker = commands
+        .spawn((
+            TextBundle::from_sections([TextSection::new(
+                "",
+                TextStyle {
+                    font_size: 40.0,
+                    color: Color::WHITE,
+                    ..default()
+                },
+            )])
+            .with_text_justify(JustifyText::Center),
+            LevelPicker,
+        ))
+        .id();
+
+    let controls_prompt = commands
+        .spawn(
+            TextBundle::from_sections([TextSection::new(
+                format!(
+                    "{} for controls",
+                    actions::describe(&bindings, Action::Controls)
+                ),
+                TextStyle {
+                    font_size: 30.0,
+                    color: Color::WHITE,
+                    ..default()
+                },
+            )])
+            .with_text_justify(JustifyText::Center),
+        )
+        .id();
+
     let parent = commands.spawn((container, WelcomeScreen)).id();
 
-    commands.entity(parent).push_children(&[header, prompt]);
+    commands
+        .entity(parent)
+        .push_children(&[header, prompt, level_picker, controls_prompt]);
 }
 
 pub fn despawn_welcome_screen(mut commands: Commands, query: Query<Entity, With<WelcomeScreen>>) {